@@ -3,7 +3,7 @@ use std::time::Duration;
 
 use ya_client::{
     activity::{ActivityProviderApi, ActivityRequestorControlApi, ActivityRequestorStateApi},
-    model::activity::ExeScriptRequest,
+    model::{activity::ExeScriptRequest, ActivityId},
     web::WebClient,
     Result,
 };
@@ -35,7 +35,7 @@ async fn requestor(client: &WebClient, agreement_id: &str) -> Result<()> {
 async fn requestor_start(
     client: &ActivityRequestorControlApi,
     agreement_id: &str,
-) -> Result<String> {
+) -> Result<ActivityId> {
     println!("[+] Activity, agreement {}", agreement_id);
     let activity_id = client.create_activity(agreement_id).await?;
     println!("[<] Activity: {}", activity_id);
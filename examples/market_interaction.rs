@@ -151,6 +151,9 @@ async fn provider_interact(options: Options, nanos: u32) -> Result<()> {
                 ProviderEvent::PropertyQueryEvent { .. } => {
                     println!("Unsupported PropertyQueryEvent.");
                 }
+                ProviderEvent::Unknown { event_type, .. } => {
+                    println!("Unsupported {}.", event_type);
+                }
             }
         }
     }
@@ -280,6 +283,9 @@ async fn requestor_interact(options: Options, nanos: u32) -> Result<()> {
                 RequestorEvent::PropertyQueryEvent { .. } => {
                     log::error!("Unsupported PropertyQueryEvent.");
                 }
+                RequestorEvent::Unknown { event_type, .. } => {
+                    log::error!("Unsupported {}.", event_type);
+                }
             }
         }
     }
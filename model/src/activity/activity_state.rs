@@ -11,6 +11,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ActivityState {
     #[serde(rename = "state")]
     pub state: StatePair,
@@ -51,6 +52,7 @@ impl From<StatePair> for ActivityState {
 #[derive(
     Clone, Copy, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StatePair(pub State, pub Option<State>);
 
 impl StatePair {
@@ -75,6 +77,7 @@ impl From<State> for StatePair {
 #[derive(
     Clone, Default, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum State {
     #[default]
     New,
@@ -11,6 +11,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ActivityUsage {
     /// Current usage vector
     #[serde(rename = "currentUsage", skip_serializing_if = "Option::is_none")]
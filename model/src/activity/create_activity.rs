@@ -2,17 +2,19 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "sgx")]
 use crate::activity::sgx_credentials::SgxCredentials;
+use crate::{ActivityId, AgreementId};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CreateActivityRequest {
     #[serde(rename = "agreementId")]
-    pub agreement_id: String,
+    pub agreement_id: AgreementId,
     #[serde(rename = "requestorPubKey", skip_serializing_if = "Option::is_none")]
     pub requestor_pub_key: Option<String>,
 }
 
 impl CreateActivityRequest {
-    pub fn new(agreement_id: String) -> CreateActivityRequest {
+    pub fn new(agreement_id: AgreementId) -> CreateActivityRequest {
         CreateActivityRequest {
             agreement_id,
             requestor_pub_key: None,
@@ -21,15 +23,16 @@ impl CreateActivityRequest {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CreateActivityResult {
     #[serde(rename = "activityId")]
-    pub activity_id: String,
+    pub activity_id: ActivityId,
     #[serde(rename = "credentials", skip_serializing_if = "Option::is_none")]
     pub credentials: Option<Credentials>,
 }
 
 impl CreateActivityResult {
-    pub fn new(activity_id: String) -> CreateActivityResult {
+    pub fn new(activity_id: ActivityId) -> CreateActivityResult {
         CreateActivityResult {
             activity_id,
             credentials: None,
@@ -38,6 +41,7 @@ impl CreateActivityResult {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 pub enum Credentials {
     #[cfg(feature = "sgx")]
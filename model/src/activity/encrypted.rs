@@ -1,4 +1,5 @@
 use crate::activity::{ExeScriptCommand, ExeScriptCommandResult, ExeScriptCommandState};
+use crate::ActivityId;
 use rand::Rng as _;
 use secp256k1::ecdh::SharedSecret;
 use secp256k1::{PublicKey, SecretKey};
@@ -6,15 +7,17 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
-    pub activity_id: String,
+    pub activity_id: ActivityId,
     pub batch_id: String,
     pub timeout: Option<f32>,
     pub command: RequestCommand,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum RequestCommand {
     Exec { exe_script: Vec<ExeScriptCommand> },
@@ -23,6 +26,7 @@ pub enum RequestCommand {
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "command")]
 pub enum Response {
@@ -34,6 +38,7 @@ pub enum Response {
 
 /// Error message for activity service bus API.
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum RpcMessageError {
     #[error("Service error: {0}")]
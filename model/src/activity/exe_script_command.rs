@@ -12,9 +12,11 @@ use crate::activity::ExeScriptCommandState;
 use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ExeScriptCommand {
     Sign {},
@@ -22,7 +24,7 @@ pub enum ExeScriptCommand {
         #[serde(default)]
         net: Vec<Network>,
         #[serde(default)]
-        hosts: HashMap<String, String>, // hostname -> IP
+        hosts: HashMap<String, IpAddr>, // hostname -> IP
 
         #[serde(default)]
         hostname: Option<String>,
@@ -61,16 +63,20 @@ pub enum ExeScriptCommand {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum VolumeMount {
     Host {},
     Ram {
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
         size: ByteSize,
     },
     Storage {
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
         size: ByteSize,
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(default)]
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
         preallocate: Option<ByteSize>,
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(default)]
@@ -79,6 +85,7 @@ pub enum VolumeMount {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Volumes {
     Simple(Vec<String>),
@@ -101,18 +108,21 @@ impl Volumes {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Network {
     pub id: String,
-    pub ip: String,
-    pub mask: Option<String>,
-    pub gateway: Option<String>,
-    pub node_ip: String,
+    pub ip: IpAddr,
+    #[serde(default, deserialize_with = "crate::net::deserialize_opt_mask")]
+    pub mask: Option<IpAddr>,
+    pub gateway: Option<IpAddr>,
+    pub node_ip: IpAddr,
     #[serde(default)]
-    pub nodes: HashMap<String, String>, // IP -> NodeId
+    pub nodes: HashMap<IpAddr, String>, // IP -> NodeId
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Capture {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -122,6 +132,7 @@ pub struct Capture {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum CaptureMode {
     AtEnd {
@@ -140,6 +151,7 @@ pub enum CaptureMode {
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum CaptureFormat {
     #[default]
@@ -150,6 +162,7 @@ pub enum CaptureFormat {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum CapturePart {
     Head(usize),
@@ -158,6 +171,7 @@ pub enum CapturePart {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TransferArgs {
     pub format: Option<String>,
     pub depth: Option<usize>,
@@ -165,16 +179,19 @@ pub struct TransferArgs {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressArgs {
     #[serde(default)]
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
     pub update_interval: Option<Duration>,
     /// Number of bytes after which next progress event will be sent.
     pub update_step: Option<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum FileSet {
     Pattern(SetEntry<String>),
@@ -182,6 +199,7 @@ pub enum FileSet {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SetObject {
     pub desc: Option<String>,
     pub includes: Option<SetEntry<String>>,
@@ -189,6 +207,7 @@ pub struct SetObject {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum SetEntry<T> {
     Single(T),
@@ -290,7 +309,7 @@ mod test {
             "/golem/input",
             "/golem/output"
         ]"#;
-        let volumes: Volumes = serde_json::from_str(&volumes_json).unwrap();
+        let volumes: Volumes = serde_json::from_str(volumes_json).unwrap();
 
         assert_eq!(
             volumes,
@@ -318,7 +337,7 @@ mod test {
                 }
             }
         }"#;
-        let volumes: Volumes = serde_json::from_str(&volumes_json).unwrap();
+        let volumes: Volumes = serde_json::from_str(volumes_json).unwrap();
 
         assert_eq!(
             volumes,
@@ -8,19 +8,26 @@
  * Generated by: https://openapi-generator.tech
  */
 
+use crate::{ActivityId, AgreementId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum_macros::EnumVariantNames;
+
+use crate::unknown_event::{deserialize_tagged_event, serialize_tagged_event};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderEvent {
-    pub activity_id: String,
-    pub agreement_id: String,
+    pub activity_id: ActivityId,
+    pub agreement_id: AgreementId,
     pub event_type: ProviderEventType,
     pub event_date: DateTime<Utc>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ProviderEventType {
     #[serde(rename = "CreateActivity")]
     CreateActivity {
@@ -29,4 +36,46 @@ pub enum ProviderEventType {
     },
     #[serde(rename = "DestroyActivity")]
     DestroyActivity {},
+    /// Event of a type unknown to this version of the client.
+    #[serde(
+        untagged,
+        deserialize_with = "deserialize_tagged_event::<ProviderEventType, _>",
+        serialize_with = "serialize_tagged_event"
+    )]
+    Unknown {
+        event_type: String,
+        /// Content of the event.
+        raw: Value,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_event_type() {
+        let event = json!({
+            "activityId": "a1",
+            "agreementId": "ag1",
+            "eventType": {"GetActivityState": {}},
+            "eventDate": "2020-12-21T15:51:21.126645Z",
+        });
+        let parsed: ProviderEvent = serde_json::from_value(event.clone()).unwrap();
+        assert_eq!(
+            parsed.event_type,
+            ProviderEventType::Unknown {
+                event_type: "GetActivityState".into(),
+                raw: json!({}),
+            }
+        );
+        assert_eq!(serde_json::to_value(parsed).unwrap(), event);
+
+        let unit = json!("GetActivityUsage");
+        let parsed: ProviderEventType = serde_json::from_value(unit.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), unit);
+
+        assert!(serde_json::from_value::<ProviderEventType>(json!("CreateActivity")).is_err());
+    }
 }
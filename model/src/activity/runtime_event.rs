@@ -1,8 +1,13 @@
 use crate::activity::{CommandOutput, ExeScriptCommand};
 use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum_macros::EnumVariantNames;
+
+use crate::unknown_event::{deserialize_tagged_event, serialize_tagged_event};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RuntimeEvent {
     pub batch_id: String,
     pub index: usize,
@@ -53,8 +58,10 @@ impl RuntimeEvent {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum RuntimeEventKind {
     Started {
         command: ExeScriptCommand,
@@ -66,9 +73,21 @@ pub enum RuntimeEventKind {
     StdOut(CommandOutput),
     StdErr(CommandOutput),
     Progress(CommandProgress),
+    /// Event of a kind unknown to this version of the client.
+    #[serde(
+        untagged,
+        deserialize_with = "deserialize_tagged_event::<RuntimeEventKind, _>",
+        serialize_with = "serialize_tagged_event"
+    )]
+    Unknown {
+        kind: String,
+        /// Content of the event.
+        raw: Value,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub struct CommandProgress {
     /// Steps are counted starting from 0. That means that first step from 4-steps tasks
@@ -82,3 +101,31 @@ pub struct CommandProgress {
     pub progress: (u64, Option<u64>),
     pub unit: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_event_kind() {
+        let event = json!({
+            "batch_id": "b1",
+            "index": 0,
+            "timestamp": "2020-12-21T15:51:21.126645",
+            "kind": {"paused": {"reason": "suspended"}},
+        });
+        let parsed: RuntimeEvent = serde_json::from_value(event.clone()).unwrap();
+        match &parsed.kind {
+            RuntimeEventKind::Unknown { kind, raw } => {
+                assert_eq!(kind, "paused");
+                assert_eq!(raw["reason"], "suspended");
+            }
+            kind => panic!("unexpected event kind: {:?}", kind),
+        }
+        assert_eq!(serde_json::to_value(parsed).unwrap(), event);
+
+        let malformed = json!({"started": {"reason": "suspended"}});
+        assert!(serde_json::from_value::<RuntimeEventKind>(malformed).is_err());
+    }
+}
@@ -2,10 +2,13 @@ use secp256k1::PublicKey;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SgxCredentials {
     #[serde(rename = "enclavePubKey")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub enclave_pub_key: PublicKey,
     #[serde(rename = "requestorPubKey")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub requestor_pub_key: PublicKey,
     #[serde(rename = "payloadHash")]
     pub payload_hash: String,
@@ -14,6 +17,7 @@ pub struct SgxCredentials {
     #[serde(rename = "iasReport")]
     pub ias_report: String,
     #[serde(rename = "iasSig", with = "binenc")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub ias_sig: Vec<u8>,
 }
 
@@ -0,0 +1,19 @@
+//! Writes JSON Schemas of the model types to a directory,
+//! as `<dir>/<module>/<type name>.json`.
+//!
+//! Usage: `dump-schema [output-dir]`; defaults to `schema`.
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+fn main() -> io::Result<()> {
+    let dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| "schema".into()));
+    for (name, schema) in ya_client_model::schema::all() {
+        let path = dir.join(format!("{}.json", name));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
@@ -11,6 +11,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(thiserror::Error, Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[error("Yagna API error: {message:?}")]
 pub struct ErrorMessage {
     pub message: Option<String>,
@@ -0,0 +1,156 @@
+//! Typed identifiers of API objects
+//!
+//! Identifiers are opaque strings assigned by the daemon. Distinct types prevent
+//! passing eg. an activity id where an agreement id is expected, while conversions
+//! from `&str` and `String` keep plain strings usable wherever an identifier is taken.
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(id: impl Into<String>) -> Self {
+                $name(id.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name(s.to_string()))
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                $name(id.to_string())
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(id: &String) -> Self {
+                $name(id.clone())
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(id: &$name) -> Self {
+                id.clone()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+id_type!(
+    /// Identifier of an Agreement.
+    AgreementId
+);
+id_type!(
+    /// Identifier of an Activity.
+    ActivityId
+);
+id_type!(
+    /// Identifier of a Demand or Offer subscription on the market.
+    SubscriptionId
+);
+id_type!(
+    /// Identifier of a payment Allocation.
+    AllocationId
+);
+id_type!(
+    /// Identifier of a Debit Note.
+    DebitNoteId
+);
+id_type!(
+    /// Identifier of an Invoice.
+    InvoiceId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_transparent() {
+        let id: AgreementId = serde_json::from_str("\"a1\"").unwrap();
+        assert_eq!(id, "a1");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"a1\"");
+    }
+
+    #[test]
+    fn conversions() {
+        let id: ActivityId = "act".parse().unwrap();
+        assert_eq!(id.to_string(), "act");
+        assert_eq!(id.len(), 3);
+        assert_eq!(ActivityId::from("act"), id);
+        assert_eq!(String::from(id), "act");
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::NodeId;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Identity {
     pub identity: NodeId,
     pub name: String,
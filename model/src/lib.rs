@@ -1,10 +1,17 @@
 pub mod activity;
 pub mod error_message;
+pub mod id;
 pub mod identity;
 pub mod market;
 pub mod net;
 pub mod node_id;
 pub mod payment;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod version;
+
+mod unknown_event;
 
 pub use error_message::ErrorMessage;
+pub use id::{ActivityId, AgreementId, AllocationId, DebitNoteId, InvoiceId, SubscriptionId};
 pub use node_id::{NodeId, ParseError};
@@ -12,13 +12,14 @@ use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 
 use crate::market::{Demand, Offer};
-use crate::NodeId;
+use crate::{AgreementId, NodeId};
 
 /// Agreement expresses the terms of the deal between Provider and Requestor.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Agreement {
     #[serde(rename = "agreementId")]
-    pub agreement_id: String,
+    pub agreement_id: AgreementId,
     #[serde(rename = "demand")]
     pub demand: Demand,
     #[serde(rename = "offer")]
@@ -49,7 +50,7 @@ pub struct Agreement {
 
 impl Agreement {
     pub fn new(
-        agreement_id: String,
+        agreement_id: AgreementId,
         demand: Demand,
         offer: Offer,
         valid_to: DateTime<Utc>,
@@ -86,6 +87,7 @@ impl Agreement {
 /// enum may be used for carrying the information whether an agreement is obtained
 /// from the requestor or the provider.
 #[derive(Clone, Copy, Debug, Display, PartialEq, Serialize, Deserialize, EnumString)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Role {
     Provider,
     Requestor,
@@ -93,9 +95,10 @@ pub enum Role {
 
 /// A short summary of an agreement returned when they're listed
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AgreementListEntry {
-    pub id: String,
+    pub id: AgreementId,
     pub timestamp: DateTime<Utc>,
     pub approved_date: Option<DateTime<Utc>>,
     pub role: Role,
@@ -122,6 +125,7 @@ pub struct AgreementListEntry {
     Deserialize,
     EnumString,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum State {
     /// Newly created by a Requestor (draft based on Proposal)
     #[serde(rename = "Proposal")]
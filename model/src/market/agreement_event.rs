@@ -8,20 +8,26 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use strum_macros::EnumVariantNames;
 
 use crate::market::Reason;
+use crate::unknown_event::deserialize_event_type;
+use crate::AgreementId;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgreementOperationEvent {
     #[serde(rename = "eventDate")]
     pub event_date: DateTime<Utc>,
     #[serde(rename = "agreementId")]
-    pub agreement_id: String,
+    pub agreement_id: AgreementId,
     #[serde(flatten)]
     pub event_type: AgreementEventType,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 #[serde(tag = "eventType")]
 pub enum AgreementEventType {
@@ -46,12 +52,51 @@ pub enum AgreementEventType {
         #[serde(rename = "reason", skip_serializing_if = "Option::is_none")]
         reason: Option<Reason>,
     },
+    /// Event of a type unknown to this version of the client.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(
+            rename = "eventType",
+            deserialize_with = "deserialize_event_type::<AgreementEventType, _>"
+        )]
+        event_type: String,
+        /// Remaining fields of the event.
+        #[serde(flatten)]
+        raw: Map<String, Value>,
+    },
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum AgreementTerminator {
     #[serde(rename = "Requestor")]
     Requestor,
     #[serde(rename = "Provider")]
     Provider,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_event_type() {
+        let event: AgreementOperationEvent = serde_json::from_value(json!({
+            "eventType": "AgreementSuspendedEvent",
+            "eventDate": "2020-12-21T15:51:21.126645Z",
+            "agreementId": "a1",
+            "until": "2020-12-22T00:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(event.agreement_id, "a1");
+        match event.event_type {
+            AgreementEventType::Unknown { event_type, raw } => {
+                assert_eq!(event_type, "AgreementSuspendedEvent");
+                assert_eq!(raw.len(), 1);
+                assert_eq!(raw["until"], "2020-12-22T00:00:00Z");
+            }
+            event_type => panic!("unexpected event type: {:?}", event_type),
+        }
+    }
+}
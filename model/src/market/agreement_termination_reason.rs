@@ -3,13 +3,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::market::AgreementTerminator;
 use crate::market::Reason;
+use crate::AgreementId;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgreementTerminationReason {
     #[serde(rename = "eventDate")]
     pub event_date: DateTime<Utc>,
     #[serde(rename = "agreementId")]
-    pub agreement_id: String,
+    pub agreement_id: AgreementId,
     #[serde(rename = "terminator")]
     pub terminator: AgreementTerminator,
     #[serde(rename = "signature")]
@@ -9,9 +9,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::NodeId;
+use crate::{NodeId, SubscriptionId};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Demand {
     /// The object which includes all the Demand properties.
     /// This is a JSON object in \"flat convention\" - where keys are full
@@ -44,7 +45,7 @@ pub struct Demand {
     #[serde(rename = "constraints")]
     pub constraints: String,
     #[serde(rename = "demandId")]
-    pub demand_id: String,
+    pub demand_id: SubscriptionId,
     #[serde(rename = "requestorId")]
     pub requestor_id: NodeId,
     /// Object creation timestamp
@@ -56,7 +57,7 @@ impl Demand {
     pub fn new(
         properties: serde_json::Value,
         constraints: String,
-        demand_id: String,
+        demand_id: SubscriptionId,
         requestor_id: NodeId,
         timestamp: DateTime<Utc>,
     ) -> Demand {
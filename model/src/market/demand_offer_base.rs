@@ -13,6 +13,7 @@ pub type NewDemand = DemandOfferBase;
 pub type NewProposal = DemandOfferBase;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DemandOfferBase {
     /// The object which includes all the Demand/Offer/Proposal properties.
     /// This is a JSON object in \"flat convention\" - where keys are full
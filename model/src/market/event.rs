@@ -8,11 +8,16 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use strum_macros::EnumVariantNames;
 
 use crate::market::{Agreement, PropertyQuery, Proposal, Reason};
+use crate::unknown_event::deserialize_event_type;
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "eventType")]
+#[allow(clippy::large_enum_variant)]
 pub enum ProviderEvent {
     #[serde(rename = "ProposalEvent")]
     ProposalEvent {
@@ -44,9 +49,22 @@ pub enum ProviderEvent {
         #[serde(rename = "propertyQuery")]
         property_query: PropertyQuery,
     },
+    /// Event of a type unknown to this version of the client.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(
+            rename = "eventType",
+            deserialize_with = "deserialize_event_type::<ProviderEvent, _>"
+        )]
+        event_type: String,
+        /// Remaining fields of the event.
+        #[serde(flatten)]
+        raw: Map<String, Value>,
+    },
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "eventType")]
 pub enum RequestorEvent {
     #[serde(rename = "ProposalEvent")]
@@ -72,4 +90,50 @@ pub enum RequestorEvent {
         #[serde(rename = "propertyQuery")]
         property_query: PropertyQuery,
     },
+    /// Event of a type unknown to this version of the client.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(
+            rename = "eventType",
+            deserialize_with = "deserialize_event_type::<RequestorEvent, _>"
+        )]
+        event_type: String,
+        /// Remaining fields of the event.
+        #[serde(flatten)]
+        raw: Map<String, Value>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_event_type() {
+        let raw = json!({
+            "eventType": "ProposalExpiredEvent",
+            "eventDate": "2020-12-21T15:51:21.126645Z",
+            "proposalId": "p1",
+        });
+        let event: RequestorEvent = serde_json::from_value(raw.clone()).unwrap();
+        match &event {
+            RequestorEvent::Unknown { event_type, raw } => {
+                assert_eq!(event_type, "ProposalExpiredEvent");
+                assert_eq!(raw["proposalId"], "p1");
+                assert!(raw.get("eventType").is_none());
+            }
+            _ => panic!("unexpected event: {:?}", event),
+        }
+        assert_eq!(serde_json::to_value(&event).unwrap(), raw);
+    }
+
+    #[test]
+    fn malformed_known_event() {
+        let result = serde_json::from_value::<ProviderEvent>(json!({
+            "eventType": "ProposalRejectedEvent",
+            "eventDate": "2020-12-21T15:51:21.126645Z",
+        }));
+        assert!(result.is_err());
+    }
 }
@@ -9,9 +9,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::NodeId;
+use crate::{NodeId, SubscriptionId};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Offer {
     /// The object which includes all the Offer properties.
     /// This is a JSON object in \"flat convention\" - where keys are full
@@ -44,7 +45,7 @@ pub struct Offer {
     #[serde(rename = "constraints")]
     pub constraints: String,
     #[serde(rename = "offerId")]
-    pub offer_id: String,
+    pub offer_id: SubscriptionId,
     #[serde(rename = "providerId")]
     pub provider_id: NodeId,
     /// Object creation timestamp
@@ -56,7 +57,7 @@ impl Offer {
     pub fn new(
         properties: serde_json::Value,
         constraints: String,
-        offer_id: String,
+        offer_id: SubscriptionId,
         provider_id: NodeId,
         timestamp: DateTime<Utc>,
     ) -> Offer {
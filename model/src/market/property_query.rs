@@ -9,6 +9,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PropertyQuery {
     #[serde(rename = "issuerProperties", skip_serializing_if = "Option::is_none")]
     pub issuer_properties: Option<serde_json::Value>,
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use crate::{ErrorMessage, NodeId};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Proposal {
     /// The object which includes all the Proposal properties.
     /// This is a JSON object in \"flat convention\" - where keys are full
@@ -90,6 +91,7 @@ impl Proposal {
 /// * `Accepted` - promoted into the Agreement draft
 /// * `Expired` - not accepted nor rejected before validity period
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum State {
     #[serde(rename = "Initial")]
     /// Proposal arrived from the market as response to subscription
@@ -5,6 +5,7 @@ use std::fmt::Debug;
 
 /// Generic Event reason information structure.
 #[derive(Clone, Display, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[display(fmt = "'{}'", message)]
 pub struct Reason {
     pub message: String,
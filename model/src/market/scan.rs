@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 /// Specify constructor data for new market scanning iterator.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NewScan {
     pub timeout: Option<u64>,
     #[serde(rename = "type")]
@@ -10,6 +11,7 @@ pub struct NewScan {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ScanType {
     Offer,
@@ -1,13 +1,17 @@
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::net::IpAddr;
 
 use crate::NodeId;
 
+pub use ipnet::IpNet;
+
 pub const NET_API_PATH: &str = "/net-api/v2";
 pub const NET_API_V1_VPN_PATH: &str = "/net-api/v1";
 pub const NET_API_V2_NET_PATH: &str = "/net-api/v2/net";
 pub const NET_API_V2_VPN_PATH: &str = "/net-api/v2/vpn";
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Status {
     pub node_id: NodeId,
@@ -17,41 +21,163 @@ pub struct Status {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Network {
     pub id: String,
-    pub ip: String,
-    pub mask: String,
-    pub gateway: String,
+    pub ip: IpAddr,
+    #[serde(deserialize_with = "deserialize_mask")]
+    pub mask: IpAddr,
+    pub gateway: IpAddr,
+}
+
+impl Network {
+    /// Network address with the prefix length of `mask`.
+    pub fn net(&self) -> Result<IpNet, InvalidNetwork> {
+        to_net(self.ip, self.mask)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct NewNetwork {
-    pub ip: String,
-    pub mask: Option<String>,
-    pub gateway: Option<String>,
+    pub ip: IpAddr,
+    #[serde(default, deserialize_with = "deserialize_opt_mask")]
+    pub mask: Option<IpAddr>,
+    pub gateway: Option<IpAddr>,
+}
+
+impl NewNetwork {
+    /// Describes the `net` subnet, with the netmask derived from its prefix length
+    /// and the first host address as the gateway.
+    pub fn new(net: IpNet) -> Self {
+        NewNetwork {
+            ip: net.network(),
+            mask: Some(net.netmask()),
+            gateway: net.hosts().next(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Node {
     pub id: String,
-    pub ip: String,
+    pub ip: IpAddr,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Address {
-    pub ip: String,
+    pub ip: IpAddr,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Connection {
     pub protocol: u16,
-    pub local_ip: String,
+    pub local_ip: IpAddr,
     pub local_port: u16,
-    pub remote_ip: String,
+    pub remote_ip: IpAddr,
     pub remote_port: u16,
 }
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum InvalidNetwork {
+    #[error("invalid netmask: {0}")]
+    Mask(IpAddr),
+    #[error("address {ip} and netmask {mask} are of different families")]
+    FamilyMismatch { ip: IpAddr, mask: IpAddr },
+}
+
+/// Returns the prefix length of a contiguous netmask.
+pub fn mask_prefix_len(mask: IpAddr) -> Result<u8, InvalidNetwork> {
+    match mask {
+        IpAddr::V4(mask) => ipnet::ipv4_mask_to_prefix(mask),
+        IpAddr::V6(mask) => ipnet::ipv6_mask_to_prefix(mask),
+    }
+    .map_err(|_| InvalidNetwork::Mask(mask))
+}
+
+/// Builds a CIDR network out of an address and a netmask.
+pub fn to_net(ip: IpAddr, mask: IpAddr) -> Result<IpNet, InvalidNetwork> {
+    if ip.is_ipv4() != mask.is_ipv4() {
+        return Err(InvalidNetwork::FamilyMismatch { ip, mask });
+    }
+    let prefix_len = mask_prefix_len(mask)?;
+    IpNet::new(ip, prefix_len).map_err(|_| InvalidNetwork::Mask(mask))
+}
+
+pub(crate) fn deserialize_mask<'de, D>(deserializer: D) -> Result<IpAddr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mask = IpAddr::deserialize(deserializer)?;
+    mask_prefix_len(mask).map_err(de::Error::custom)?;
+    Ok(mask)
+}
+
+pub(crate) fn deserialize_opt_mask<'de, D>(deserializer: D) -> Result<Option<IpAddr>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mask = Option::<IpAddr>::deserialize(deserializer)?;
+    if let Some(mask) = mask {
+        mask_prefix_len(mask).map_err(de::Error::custom)?;
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn new_network_from_cidr() {
+        let network = NewNetwork::new("192.168.8.0/22".parse().unwrap());
+        assert_eq!(
+            serde_json::to_value(&network).unwrap(),
+            json!({"ip": "192.168.8.0", "mask": "255.255.252.0", "gateway": "192.168.8.1"})
+        );
+    }
+
+    #[test]
+    fn network_net() {
+        let network: Network = serde_json::from_value(json!({
+            "id": "net",
+            "ip": "10.0.0.0",
+            "mask": "255.255.0.0",
+            "gateway": "10.0.0.1",
+        }))
+        .unwrap();
+        assert_eq!(
+            network.net().unwrap(),
+            "10.0.0.0/16".parse::<IpNet>().unwrap()
+        );
+
+        let mismatch = Network {
+            mask: "ffff::".parse().unwrap(),
+            ..network
+        };
+        assert!(matches!(
+            mismatch.net(),
+            Err(InvalidNetwork::FamilyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        assert!(serde_json::from_value::<Address>(json!({"ip": "10.0.0"})).is_err());
+        assert!(serde_json::from_value::<NewNetwork>(
+            json!({"ip": "10.0.0.0", "mask": "255.0.255.0"})
+        )
+        .is_err());
+        let network: NewNetwork = serde_json::from_value(json!({"ip": "10.0.0.0"})).unwrap();
+        assert_eq!(network.mask, None);
+    }
+}
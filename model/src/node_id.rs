@@ -3,10 +3,15 @@ use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::str::FromStr;
 use std::{fmt, str};
+use tiny_keccak::{Hasher, Keccak};
+
+#[cfg(feature = "crypto")]
+pub use crypto::{parse_signature, serialize_signature};
 
 const NODE_ID_LENGTH: usize = 20;
 
 #[derive(Clone, Debug, thiserror::Error, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[error("NodeId `{original_str}` parsing error: {msg}")]
 pub struct ParseError {
     original_str: String,
@@ -23,6 +28,7 @@ impl ParseError {
 }
 
 #[derive(Clone, Debug, thiserror::Error, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[error("NodeId parsing error: {msg}")]
 pub struct InvalidLengthError {
     msg: String,
@@ -63,6 +69,59 @@ impl NodeId {
     pub fn into_array(self) -> [u8; NODE_ID_LENGTH] {
         self.inner
     }
+
+    /// Formats the id as an [EIP-55](https://eips.ethereum.org/EIPS/eip-55) checksummed address.
+    ///
+    /// Same as formatting with `{:#}`.
+    pub fn to_checksum_string(&self) -> String {
+        self.with_hex(checksummed)
+    }
+
+    /// Parses the id, validating the EIP-55 checksum of mixed-case addresses.
+    ///
+    /// Addresses in a single letter case carry no checksum and are accepted, as with
+    /// [`FromStr`].
+    pub fn from_str_strict(s: &str) -> Result<Self, ParseError> {
+        let node_id = NodeId::from_str(s)?;
+        let digits = &s[2..];
+        let mixed_case = digits.bytes().any(|b| b.is_ascii_lowercase())
+            && digits.bytes().any(|b| b.is_ascii_uppercase());
+        if mixed_case && node_id.to_checksum_string() != s {
+            return Err(ParseError::new(s, "invalid EIP-55 checksum"));
+        }
+        Ok(node_id)
+    }
+
+    /// Checks whether `address` denotes this id, regardless of letter case.
+    ///
+    /// Useful for comparing with addresses held as strings, eg. `payer_addr` of a `Payment`.
+    pub fn is_address(&self, address: &str) -> bool {
+        NodeId::from_str(address).is_ok_and(|node_id| node_id == *self)
+    }
+}
+
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Applies the EIP-55 checksum to a lowercase `0x` prefixed address.
+fn checksummed(hex_str: &str) -> String {
+    let digits = &hex_str[2..];
+    let hash = keccak256(digits.as_bytes());
+    let mut out = String::with_capacity(hex_str.len());
+    out.push_str("0x");
+    for (i, c) in digits.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+        match nibble >= 8 {
+            true => out.push(c.to_ascii_uppercase()),
+            false => out.push(c),
+        }
+    }
+    out
 }
 
 impl Default for NodeId {
@@ -186,7 +245,10 @@ impl fmt::Debug for NodeId {
 
 impl fmt::Display for NodeId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.with_hex(|hex_str| write!(f, "{}", hex_str))
+        match f.alternate() {
+            true => f.write_str(&self.to_checksum_string()),
+            false => self.with_hex(|hex_str| write!(f, "{}", hex_str)),
+        }
     }
 }
 
@@ -232,6 +294,149 @@ impl<'de> Deserialize<'de> for NodeId {
     }
 }
 
+#[cfg(feature = "schema")]
+mod schema {
+    use super::NodeId;
+    use schemars::gen::SchemaGenerator;
+    use schemars::schema::{InstanceType, Schema, SchemaObject, StringValidation};
+    use schemars::JsonSchema;
+
+    impl JsonSchema for NodeId {
+        fn schema_name() -> String {
+            "NodeId".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            SchemaObject {
+                instance_type: Some(InstanceType::String.into()),
+                string: Some(Box::new(StringValidation {
+                    pattern: Some("^0x[0-9a-fA-F]{40}$".into()),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            .into()
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+mod crypto {
+    use super::{NodeId, NODE_ID_LENGTH};
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+    use secp256k1::{Message, PublicKey, Secp256k1};
+
+    impl NodeId {
+        /// Derives the id from a public key, as the last 20 bytes of the Keccak-256 hash
+        /// of the uncompressed key.
+        pub fn from_public_key(key: &PublicKey) -> Self {
+            let hash = super::keccak256(&key.serialize_uncompressed()[1..]);
+            NodeId::from(&hash[32 - NODE_ID_LENGTH..])
+        }
+
+        /// Recovers the id of the signer of a message digest.
+        pub fn recover(
+            message: &Message,
+            signature: &RecoverableSignature,
+        ) -> Result<Self, secp256k1::Error> {
+            let key = Secp256k1::verification_only().recover_ecdsa(message, signature)?;
+            Ok(NodeId::from_public_key(&key))
+        }
+
+        /// Recovers the id of the signer of a message digest from a signature in
+        /// the [`parse_signature`] format.
+        pub fn recover_from_bytes(
+            digest: &[u8],
+            signature: &[u8],
+        ) -> Result<Self, secp256k1::Error> {
+            let message = Message::from_slice(digest)?;
+            NodeId::recover(&message, &parse_signature(signature)?)
+        }
+    }
+
+    impl From<PublicKey> for NodeId {
+        fn from(key: PublicKey) -> Self {
+            NodeId::from_public_key(&key)
+        }
+    }
+
+    /// Parses a 65 byte recoverable signature, laid out as `v || r || s`, as produced
+    /// by the identity service of the daemon.
+    ///
+    /// The recovery id `v` is accepted both as `0`/`1` and in the Ethereum `27`/`28` form.
+    pub fn parse_signature(bytes: &[u8]) -> Result<RecoverableSignature, secp256k1::Error> {
+        let (v, rs) = match bytes.split_first() {
+            Some((v, rs)) if rs.len() == 64 => (*v, rs),
+            _ => return Err(secp256k1::Error::InvalidSignature),
+        };
+        let v = match v {
+            27 | 28 => v - 27,
+            v => v,
+        };
+        let recovery_id = RecoveryId::from_i32(v as i32)?;
+        RecoverableSignature::from_compact(rs, recovery_id)
+    }
+
+    /// Serializes a recoverable signature as `v || r || s`, with `v` being `0` or `1`.
+    pub fn serialize_signature(signature: &RecoverableSignature) -> [u8; 65] {
+        let (recovery_id, rs) = signature.serialize_compact();
+        let mut bytes = [0u8; 65];
+        bytes[0] = recovery_id.to_i32() as u8;
+        bytes[1..].copy_from_slice(&rs);
+        bytes
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use secp256k1::SecretKey;
+
+        fn secret_key() -> SecretKey {
+            // Private key from the EIP-155 example transaction
+            SecretKey::from_slice(&[0x46; 32]).unwrap()
+        }
+
+        #[test]
+        fn from_public_key() {
+            let key = secret_key().public_key(&Secp256k1::signing_only());
+            assert_eq!(
+                NodeId::from(key).to_checksum_string(),
+                "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+            );
+        }
+
+        #[test]
+        fn recover_signer() {
+            let message = Message::from_slice(&[7; 32]).unwrap();
+            let signature =
+                Secp256k1::signing_only().sign_ecdsa_recoverable(&message, &secret_key());
+            let signer = NodeId::recover(&message, &signature).unwrap();
+            assert_eq!(
+                signer.to_string(),
+                "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+            );
+
+            let mut bytes = serialize_signature(&signature);
+            assert_eq!(parse_signature(&bytes).unwrap(), signature);
+            assert_eq!(
+                NodeId::recover_from_bytes(&[7; 32], &bytes).unwrap(),
+                signer
+            );
+            bytes[0] += 27;
+            assert_eq!(
+                NodeId::recover_from_bytes(&[7; 32], &bytes).unwrap(),
+                signer
+            );
+
+            assert_ne!(
+                NodeId::recover_from_bytes(&[8; 32], &bytes).unwrap(),
+                signer
+            );
+            assert!(parse_signature(&bytes[..64]).is_err());
+        }
+    }
+}
+
 #[cfg(feature = "with-diesel")]
 #[allow(dead_code)]
 mod sql {
@@ -355,6 +560,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checksum_display() {
+        // Test vectors from EIP-55
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let node_id: NodeId = address.to_lowercase().parse().unwrap();
+            assert_eq!(node_id.to_checksum_string(), address);
+            assert_eq!(format!("{node_id:#}"), address);
+            assert_eq!(node_id.to_string(), address.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn parse_strict() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let node_id = NodeId::from_str_strict(checksummed).unwrap();
+        assert_eq!(
+            NodeId::from_str_strict(&checksummed.to_lowercase()).unwrap(),
+            node_id
+        );
+        assert_eq!(
+            NodeId::from_str_strict("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED").unwrap(),
+            node_id
+        );
+        assert_eq!(
+            NodeId::from_str_strict("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+                .unwrap_err()
+                .to_string(),
+            "NodeId `0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed` parsing error: invalid EIP-55 checksum"
+        );
+        assert!("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+            .parse::<NodeId>()
+            .is_ok());
+    }
+
+    #[test]
+    fn compare_with_address() {
+        let node_id: NodeId = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            .parse()
+            .unwrap();
+        assert!(node_id.is_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(node_id.is_address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"));
+        assert!(!node_id.is_address("0xbabe000000000000000000000000000000000000"));
+        assert!(!node_id.is_address("not an address"));
+    }
+
     #[test]
     fn try_from_too_long_vec() {
         let test_vec: Vec<u8> = vec![
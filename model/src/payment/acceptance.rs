@@ -1,17 +1,23 @@
+use crate::AllocationId;
 use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Acceptance {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount_accepted: BigDecimal,
-    pub allocation_id: String,
+    pub allocation_id: AllocationId,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DebitNoteAcceptance {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount_accepted: BigDecimal,
-    pub allocation_id: String,
+    pub allocation_id: AllocationId,
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
     pub auto_accept_to: Option<BigDecimal>,
 }
@@ -1,11 +1,14 @@
+use crate::{ActivityId, AllocationId};
 use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ActivityPayment {
-    pub activity_id: String,
+    pub activity_id: ActivityId,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub amount: BigDecimal,
     #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub allocation_id: Option<String>,
+    pub allocation_id: Option<AllocationId>,
 }
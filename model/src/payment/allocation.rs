@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::{ActivityId, AgreementId, AllocationId};
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -7,6 +8,7 @@ use serde_with::*;
 use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ValidateDepositCall {
     #[serde(flatten)]
@@ -14,6 +16,7 @@ pub struct ValidateDepositCall {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Deposit {
     pub id: String,
@@ -22,6 +25,7 @@ pub struct Deposit {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DepositUpdate {
     pub validate: Option<ValidateDepositCall>,
@@ -30,25 +34,32 @@ pub struct DepositUpdate {
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AllocationExpenditure {
-    pub allocation_id: String,
-    pub agreement_id: String,
-    pub activity_id: Option<String>,
+    pub allocation_id: AllocationId,
+    pub agreement_id: AgreementId,
+    pub activity_id: Option<ActivityId>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub accepted_amount: BigDecimal,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub scheduled_amount: BigDecimal,
 }
 
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Allocation {
-    pub allocation_id: String,
+    pub allocation_id: AllocationId,
     pub address: String,
     pub payment_platform: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount: BigDecimal,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub spent_amount: BigDecimal,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub remaining_amount: BigDecimal,
     pub timestamp: DateTime<Utc>,
     pub timeout: Option<DateTime<Utc>>,
@@ -62,6 +73,7 @@ pub struct Allocation {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentPlatform {
     pub driver: Option<String>,
@@ -70,6 +82,7 @@ pub struct PaymentPlatform {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase", untagged)]
 pub enum PaymentPlatformEnum {
     PaymentPlatformName(String),
@@ -78,10 +91,12 @@ pub enum PaymentPlatformEnum {
 
 #[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct NewAllocation {
     pub address: Option<String>,
     pub payment_platform: Option<PaymentPlatformEnum>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount: BigDecimal,
     pub timeout: Option<DateTime<Utc>>,
     pub deposit: Option<Deposit>,
@@ -92,9 +107,11 @@ pub struct NewAllocation {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AllocationUpdate {
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
     pub total_amount: Option<BigDecimal>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub timeout: Option<DateTime<Utc>>,
@@ -140,7 +157,7 @@ mod test {
     #[test]
     fn test_allocation() {
         let j = serde_json::to_string(&Allocation {
-            allocation_id: "".to_string(),
+            allocation_id: "".into(),
             address: "".to_string(),
             payment_platform: "".to_string(),
             total_amount: Default::default(),
@@ -1,22 +1,24 @@
-use crate::NodeId;
+use crate::{ActivityId, AgreementId, DebitNoteId, NodeId};
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DebitNote {
-    pub debit_note_id: String,
+    pub debit_note_id: DebitNoteId,
     pub issuer_id: NodeId,
     pub recipient_id: NodeId,
     pub payee_addr: String,
     pub payer_addr: String,
     pub payment_platform: String,
     #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub previous_debit_note_id: Option<String>,
+    pub previous_debit_note_id: Option<DebitNoteId>,
     pub timestamp: DateTime<Utc>,
-    pub agreement_id: String,
-    pub activity_id: String,
+    pub agreement_id: AgreementId,
+    pub activity_id: ActivityId,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount_due: BigDecimal,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub usage_counter_vector: Option<serde_json::Value>,
@@ -26,9 +28,11 @@ pub struct DebitNote {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct NewDebitNote {
-    pub activity_id: String,
+    pub activity_id: ActivityId,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount_due: BigDecimal,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub usage_counter_vector: Option<serde_json::Value>,
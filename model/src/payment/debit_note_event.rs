@@ -1,19 +1,24 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use strum_macros::Display;
+use serde_json::{Map, Value};
+use strum_macros::{Display, EnumVariantNames};
 
 use super::DriverStatusProperty;
+use crate::unknown_event::deserialize_event_type;
+use crate::DebitNoteId;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DebitNoteEvent {
-    pub debit_note_id: String,
+    pub debit_note_id: DebitNoteId,
     pub event_date: DateTime<Utc>,
     #[serde(flatten)]
     pub event_type: DebitNoteEventType,
 }
 
-#[derive(Clone, Debug, Display, Serialize, Deserialize)]
+#[derive(Clone, Debug, Display, Serialize, Deserialize, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "eventType")]
 pub enum DebitNoteEventType {
     DebitNoteReceivedEvent,
@@ -27,6 +32,18 @@ pub enum DebitNoteEventType {
         property: DriverStatusProperty,
     },
     DebitNotePaymentOkEvent,
+    /// Event of a type unknown to this version of the client.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(
+            rename = "eventType",
+            deserialize_with = "deserialize_event_type::<DebitNoteEventType, _>"
+        )]
+        event_type: String,
+        /// Remaining fields of the event.
+        #[serde(flatten)]
+        raw: Map<String, Value>,
+    },
 }
 
 impl DebitNoteEventType {
@@ -40,6 +57,7 @@ impl DebitNoteEventType {
             DebitNoteSettledEvent => "SETTLED",
             DebitNotePaymentStatusEvent { .. } => "PAYMENT_EVENT",
             DebitNotePaymentOkEvent => "PAYMENT_OK",
+            Unknown { .. } => "UNKNOWN",
         }
     }
 
@@ -50,6 +68,7 @@ impl DebitNoteEventType {
         match self {
             DebitNoteRejectedEvent { rejection } => to_value(rejection).ok(),
             DebitNotePaymentStatusEvent { property } => to_value(property).ok(),
+            Unknown { .. } => to_value(self).ok(),
             _ => None,
         }
     }
@@ -73,6 +92,7 @@ impl DebitNoteEventType {
                 property: from_value(details).ok()?,
             },
             ("PAYMENT_OK", _) => DebitNotePaymentOkEvent,
+            ("UNKNOWN", Some(details)) => from_value(details).ok()?,
             _ => None?,
         })
     }
@@ -87,7 +107,7 @@ mod test {
     #[test]
     fn test_serialize_rejected_event_has_flat_rejection() {
         let ie = DebitNoteEvent {
-            debit_note_id: "ajdik".to_string(),
+            debit_note_id: "ajdik".into(),
             event_date: DateTime::parse_from_str("2020-12-21T15:51:21.126645Z", "%+")
                 .unwrap()
                 .with_timezone(&Utc),
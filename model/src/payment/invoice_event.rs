@@ -1,19 +1,24 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use strum_macros::Display;
+use serde_json::{Map, Value};
+use strum_macros::{Display, EnumVariantNames};
 
 use super::DriverStatusProperty;
+use crate::unknown_event::deserialize_event_type;
+use crate::InvoiceId;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct InvoiceEvent {
-    pub invoice_id: String,
+    pub invoice_id: InvoiceId,
     pub event_date: DateTime<Utc>,
     #[serde(flatten)]
     pub event_type: InvoiceEventType,
 }
 
-#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, EnumVariantNames)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "eventType")]
 pub enum InvoiceEventType {
     InvoiceReceivedEvent,
@@ -27,6 +32,18 @@ pub enum InvoiceEventType {
         property: DriverStatusProperty,
     },
     InvoicePaymentOkEvent,
+    /// Event of a type unknown to this version of the client.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(
+            rename = "eventType",
+            deserialize_with = "deserialize_event_type::<InvoiceEventType, _>"
+        )]
+        event_type: String,
+        /// Remaining fields of the event.
+        #[serde(flatten)]
+        raw: Map<String, Value>,
+    },
 }
 
 impl InvoiceEventType {
@@ -40,6 +57,7 @@ impl InvoiceEventType {
             InvoiceSettledEvent => "SETTLED",
             InvoicePaymentStatusEvent { .. } => "PAYMENT_EVENT",
             InvoicePaymentOkEvent => "PAYMENT_OK",
+            Unknown { .. } => "UNKNOWN",
         }
     }
 
@@ -50,6 +68,7 @@ impl InvoiceEventType {
         match self {
             InvoiceRejectedEvent { rejection } => to_value(rejection).ok(),
             InvoicePaymentStatusEvent { property } => to_value(property).ok(),
+            Unknown { .. } => to_value(self).ok(),
             _ => None,
         }
     }
@@ -73,6 +92,7 @@ impl InvoiceEventType {
                 property: from_value(details).ok()?,
             },
             ("PAYMENT_OK", _) => InvoicePaymentOkEvent,
+            ("UNKNOWN", Some(details)) => from_value(details).ok()?,
             _ => None?,
         })
     }
@@ -87,7 +107,7 @@ mod test {
     #[test]
     fn test_serialize_rejected_event_has_flat_rejection() {
         let ie = InvoiceEvent {
-            invoice_id: "ajdik".to_string(),
+            invoice_id: "ajdik".into(),
             event_date: DateTime::parse_from_str("2020-12-21T15:51:21.126645Z", "%+")
                 .unwrap()
                 .with_timezone(&Utc),
@@ -126,7 +146,7 @@ mod test {
 
         assert_eq!(
             InvoiceEvent {
-                invoice_id: "ajdik".to_string(),
+                invoice_id: "ajdik".into(),
                 event_date: DateTime::parse_from_str("2020-12-21T15:51:21.126645Z", "%+")
                     .unwrap()
                     .with_timezone(&Utc),
@@ -177,4 +197,28 @@ mod test {
             "SETTLED"
         );
     }
+
+    #[test]
+    fn test_unknown_event_type() {
+        let ie: InvoiceEvent = serde_json::from_str(
+            "{\
+                \"invoiceId\":\"ajdik\",\
+                \"eventDate\":\"2020-12-21T15:51:21.126645Z\",\
+                \"eventType\":\"InvoiceDisputedEvent\",\
+                \"dispute\":{\"reason\":\"late\"}\
+            }",
+        )
+        .unwrap();
+        let iet = ie.event_type;
+        assert!(matches!(
+            &iet,
+            InvoiceEventType::Unknown { event_type, raw }
+                if event_type == "InvoiceDisputedEvent" && raw["dispute"]["reason"] == "late"
+        ));
+        assert_eq!(iet.discriminant(), "UNKNOWN");
+        assert_eq!(
+            InvoiceEventType::from_discriminant_and_details("UNKNOWN", iet.details()),
+            Some(iet)
+        );
+    }
 }
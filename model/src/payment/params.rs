@@ -5,11 +5,13 @@ pub const DEFAULT_ACK_TIMEOUT: f64 = 15.0; // seconds
 pub const DEFAULT_EVENT_TIMEOUT: f64 = 5.0; // seconds
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DebitNoteId {
     pub debit_note_id: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DebitNotePaymentsParams {
     pub debit_note_id: String,
@@ -20,27 +22,32 @@ pub struct DebitNotePaymentsParams {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct InvoiceId {
     pub invoice_id: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AllocationId {
     pub allocation_id: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PaymentId {
     pub payment_id: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Timeout {
     #[serde(default)]
     pub timeout: Option<f64>,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct EventParams {
     #[serde(default)]
@@ -54,6 +61,7 @@ pub struct EventParams {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct FilterParams {
     #[serde(default)]
@@ -65,6 +73,7 @@ pub struct FilterParams {
 // Event params has to be flattened by hand due to bug in actix Params serialization
 // https://github.com/nox/serde_urlencoded/issues/33
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DriverNetworkParams {
     #[serde(default)]
@@ -82,6 +91,7 @@ pub struct DriverNetworkParams {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DriverStatusParams {
     #[serde(default)]
     pub network: Option<String>,
@@ -90,6 +100,7 @@ pub struct DriverStatusParams {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AllocationIds {
     #[serde(
         rename = "allocationIds",
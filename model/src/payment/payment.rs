@@ -5,6 +5,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Signed<T> {
     #[serde(flatten)]
@@ -14,15 +15,19 @@ pub struct Signed<T> {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Signature {
     #[serde(with = "serde_bytes")]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<u8>"))]
     pub signature: Vec<u8>,
     #[serde(with = "serde_bytes")]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<u8>"))]
     pub signed_bytes: Vec<u8>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Payment {
     pub payment_id: String,
@@ -31,6 +36,7 @@ pub struct Payment {
     pub payer_addr: String,
     pub payee_addr: String,
     pub payment_platform: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub amount: BigDecimal,
     pub timestamp: DateTime<Utc>,
     pub agreement_payments: Vec<AgreementPayment>,
@@ -39,6 +45,7 @@ pub struct Payment {
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "kind")]
 pub enum DriverStatusProperty {
     InsufficientGas {
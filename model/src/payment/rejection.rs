@@ -2,9 +2,11 @@ use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Rejection {
     pub rejection_reason: crate::payment::RejectionReason,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub total_amount_accepted: BigDecimal,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub message: Option<String>,
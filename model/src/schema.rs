@@ -0,0 +1,123 @@
+//! JSON Schemas of the model types
+//!
+//! Schemas are derived from the serde attributes of the types,
+//! so they describe the JSON representation used by the REST API.
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+
+use crate::{activity, market, net, payment};
+
+fn schema<T: JsonSchema>(module: &str) -> (String, RootSchema) {
+    (format!("{}/{}", module, T::schema_name()), schema_for!(T))
+}
+
+/// Schemas of all API payload types, keyed by `<module>/<type name>`.
+pub fn all() -> Vec<(String, RootSchema)> {
+    vec![
+        schema::<crate::ErrorMessage>("common"),
+        schema::<crate::NodeId>("common"),
+        schema::<crate::identity::Identity>("identity"),
+        schema::<crate::version::Version>("version"),
+        schema::<crate::version::VersionInfo>("version"),
+        schema::<activity::ActivityState>("activity"),
+        schema::<activity::ActivityUsage>("activity"),
+        schema::<activity::CreateActivityRequest>("activity"),
+        schema::<activity::CreateActivityResult>("activity"),
+        schema::<activity::ExeScriptCommand>("activity"),
+        schema::<activity::ExeScriptCommandResult>("activity"),
+        schema::<activity::ExeScriptCommandState>("activity"),
+        schema::<activity::ExeScriptRequest>("activity"),
+        schema::<activity::ProviderEvent>("activity"),
+        schema::<activity::RuntimeEvent>("activity"),
+        schema::<market::Agreement>("market"),
+        schema::<market::AgreementListEntry>("market"),
+        schema::<market::AgreementOperationEvent>("market"),
+        schema::<market::AgreementProposal>("market"),
+        schema::<market::AgreementTerminationReason>("market"),
+        schema::<market::Demand>("market"),
+        schema::<market::NewDemand>("market"),
+        schema::<market::Offer>("market"),
+        schema::<market::PropertyQuery>("market"),
+        schema::<market::Proposal>("market"),
+        schema::<market::ProviderEvent>("market"),
+        schema::<market::Reason>("market"),
+        schema::<market::RequestorEvent>("market"),
+        schema::<market::scan::NewScan>("market"),
+        schema::<net::Address>("net"),
+        schema::<net::Connection>("net"),
+        schema::<net::Network>("net"),
+        schema::<net::NewNetwork>("net"),
+        schema::<net::Node>("net"),
+        schema::<net::Status>("net"),
+        schema::<payment::Acceptance>("payment"),
+        schema::<payment::Account>("payment"),
+        schema::<payment::Allocation>("payment"),
+        schema::<payment::AllocationUpdate>("payment"),
+        schema::<payment::DebitNote>("payment"),
+        schema::<payment::DebitNoteEvent>("payment"),
+        schema::<payment::DriverDetails>("payment"),
+        schema::<payment::DriverStatusProperty>("payment"),
+        schema::<payment::Invoice>("payment"),
+        schema::<payment::InvoiceEvent>("payment"),
+        schema::<payment::MarketDecoration>("payment"),
+        schema::<payment::NewAllocation>("payment"),
+        schema::<payment::NewDebitNote>("payment"),
+        schema::<payment::NewInvoice>("payment"),
+        schema::<payment::Payment>("payment"),
+        schema::<payment::Rejection>("payment"),
+        schema::<payment::Signed<payment::Payment>>("payment"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn json<T: JsonSchema>() -> Value {
+        serde_json::to_value(schema_for!(T)).unwrap()
+    }
+
+    #[test]
+    fn camel_case_fields() {
+        let schema = json::<payment::NewAllocation>();
+        let properties = &schema["properties"];
+        assert_eq!(properties["totalAmount"]["type"], "string");
+        assert!(properties["paymentPlatform"].is_object());
+        assert!(properties["total_amount"].is_null());
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"totalAmount".into()));
+    }
+
+    #[test]
+    fn untagged_volumes() {
+        let schema = json::<activity::ExeScriptCommand>();
+        let volumes = &schema["definitions"]["Volumes"];
+        assert_eq!(volumes["anyOf"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn tagged_events() {
+        let schema = json::<market::RequestorEvent>();
+        let variants = schema["oneOf"].as_array().unwrap();
+        let tags: Vec<_> = variants
+            .iter()
+            .filter_map(|v| v["properties"]["eventType"]["enum"][0].as_str())
+            .collect();
+        assert!(tags.contains(&"ProposalEvent"));
+        assert!(tags.contains(&"PropertyQueryEvent"));
+        // unknown event fallback
+        assert_eq!(tags.len(), variants.len() - 1);
+    }
+
+    #[test]
+    fn all_schemas_have_unique_names() {
+        let schemas = all();
+        let mut names: Vec<_> = schemas.iter().map(|(name, _)| name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), schemas.len());
+    }
+}
@@ -0,0 +1,65 @@
+//! Fallback for events of types introduced in newer versions of the API
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{SerializeMap, Serializer};
+use serde_json::Value;
+use strum::VariantNames;
+
+/// Name of the fallback variant of event enums.
+const UNKNOWN: &str = "Unknown";
+
+/// Deserializes the type of an event which is not one of the variants of `T`.
+///
+/// Used on the untagged `Unknown` variant of event enums, so that malformed
+/// events of known types fail instead of falling back to it.
+pub(crate) fn deserialize_event_type<'de, T, D>(deserializer: D) -> Result<String, D::Error>
+where
+    T: VariantNames,
+    D: Deserializer<'de>,
+{
+    let event_type = String::deserialize(deserializer)?;
+    check_event_type::<T, D::Error>(&event_type)?;
+    Ok(event_type)
+}
+
+/// Deserializes an externally tagged event, ie. `"Type"` or `{"Type": content}`,
+/// which is not one of the variants of `T` into its type and content.
+pub(crate) fn deserialize_tagged_event<'de, T, D>(
+    deserializer: D,
+) -> Result<(String, Value), D::Error>
+where
+    T: VariantNames,
+    D: Deserializer<'de>,
+{
+    let (event_type, content) = match Value::deserialize(deserializer)? {
+        Value::String(event_type) => (event_type, Value::Null),
+        Value::Object(object) if object.len() == 1 => object.into_iter().next().unwrap(),
+        _ => {
+            return Err(D::Error::custom(
+                "expected event type or map with a single key",
+            ))
+        }
+    };
+    check_event_type::<T, D::Error>(&event_type)?;
+    Ok((event_type, content))
+}
+
+/// Serializes an event deserialized with [`deserialize_tagged_event`].
+pub(crate) fn serialize_tagged_event<S: Serializer>(
+    event_type: &String,
+    content: &Value,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if content.is_null() {
+        return serializer.serialize_str(event_type);
+    }
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry(event_type, content)?;
+    map.end()
+}
+
+fn check_event_type<T: VariantNames, E: Error>(event_type: &str) -> Result<(), E> {
+    if event_type != UNKNOWN && T::VARIANTS.contains(&event_type) {
+        return Err(E::custom(format!("malformed {}", event_type)));
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION_API_PATH: &str = "/version";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub version: String,
+    pub name: String,
+    pub seen: bool,
+    pub release_ts: NaiveDateTime,
+    pub insertion_ts: Option<NaiveDateTime>,
+    pub update_ts: Option<NaiveDateTime>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    pub current: VersionInfo,
+    pub pending: Option<VersionInfo>,
+}
@@ -1,7 +1,10 @@
 //! Provider part of the Activity API
 use ya_client_model::activity::{ActivityState, ActivityUsage, ProviderEvent, ACTIVITY_API_PATH};
+use ya_client_model::{ActivityId, AgreementId};
 
-use crate::{web::default_on_timeout, web::WebClient, web::WebInterface, Result};
+use crate::{
+    web::default_on_timeout, web::timeout_secs, web::WebClient, web::WebInterface, Result,
+};
 use chrono::{DateTime, Utc};
 use std::time::Duration;
 
@@ -22,30 +25,47 @@ impl WebInterface for ActivityProviderApi {
 /// Bindings for Provider part of the Activity API.
 impl ActivityProviderApi {
     /// Fetch list of activity_ids
-    pub async fn get_activity_ids(&self) -> Result<Vec<String>> {
+    pub async fn get_activity_ids(&self) -> Result<Vec<ActivityId>> {
         self.client.get("activity").send().json().await
     }
 
     /// Fetch activity state (which may include error details)
-    pub async fn get_activity_state(&self, activity_id: &str) -> Result<ActivityState> {
+    pub async fn get_activity_state(
+        &self,
+        activity_id: impl Into<ActivityId>,
+    ) -> Result<ActivityState> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/state");
         self.client.get(&uri).send().json().await
     }
 
     /// Set state of specified Activity.
-    pub async fn set_activity_state(&self, activity_id: &str, state: &ActivityState) -> Result<()> {
+    pub async fn set_activity_state(
+        &self,
+        activity_id: impl Into<ActivityId>,
+        state: &ActivityState,
+    ) -> Result<()> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/state");
         self.client.put(&uri).send_json(&state).json().await
     }
 
     /// Fetch current activity usage (which may include error details)
-    pub async fn get_activity_usage(&self, activity_id: &str) -> Result<ActivityUsage> {
+    pub async fn get_activity_usage(
+        &self,
+        activity_id: impl Into<ActivityId>,
+    ) -> Result<ActivityUsage> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/usage");
         self.client.get(&uri).send().json().await
     }
 
     /// Get agreement corresponding to the activity
-    pub async fn get_activity_agreement(&self, activity_id: &str) -> Result<String> {
+    pub async fn get_activity_agreement(
+        &self,
+        activity_id: impl Into<ActivityId>,
+    ) -> Result<AgreementId> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/agreement");
         self.client.get(&uri).send().json().await
     }
@@ -69,6 +89,12 @@ impl ActivityProviderApi {
             #[query] max_events,
         );
 
-        self.client.get(&url).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .events()
+            .await
+            .or_else(default_on_timeout)
     }
 }
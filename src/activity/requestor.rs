@@ -2,6 +2,7 @@
 use crate::web::{WebClient, WebInterface};
 use crate::Result;
 use ya_client_model::activity::ACTIVITY_API_PATH;
+use ya_client_model::{ActivityId, AgreementId};
 
 pub mod control;
 pub mod state;
@@ -36,7 +37,8 @@ impl ActivityRequestorApi {
     }
 
     /// Get agreement corresponding to the activity
-    pub async fn get_agreement(&self, activity_id: &str) -> Result<String> {
+    pub async fn get_agreement(&self, activity_id: impl Into<ActivityId>) -> Result<AgreementId> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/agreement");
         self.client.get(&uri).send().json().await
     }
@@ -3,8 +3,9 @@ use ya_client_model::activity::{
     CreateActivityRequest, CreateActivityResult, ExeScriptCommandResult, ExeScriptRequest,
     RuntimeEvent, ACTIVITY_API_PATH,
 };
+use ya_client_model::{ActivityId, AgreementId};
 
-use crate::web::{default_on_timeout, Event, WebClient, WebInterface};
+use crate::web::{default_on_timeout, timeout_secs, Event, WebClient, WebInterface};
 use crate::{Error, Result};
 use futures::{Stream, StreamExt};
 use std::convert::TryFrom;
@@ -26,8 +27,11 @@ impl WebInterface for ActivityRequestorControlApi {
 
 impl ActivityRequestorControlApi {
     /// Creates new Activity based on given Agreement.
-    pub async fn create_activity(&self, agreement_id: &str) -> Result<String> {
-        let r = CreateActivityRequest::new(agreement_id.to_owned());
+    pub async fn create_activity(
+        &self,
+        agreement_id: impl Into<AgreementId>,
+    ) -> Result<ActivityId> {
+        let r = CreateActivityRequest::new(agreement_id.into());
         let result: CreateActivityResult =
             self.client.post("activity").send_json(&r).json().await?;
         Ok(result.activity_id)
@@ -36,38 +40,42 @@ impl ActivityRequestorControlApi {
     #[cfg(feature = "sgx")]
     pub async fn create_secure_activity_raw(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         pub_key: secp256k1::PublicKey,
     ) -> Result<CreateActivityResult> {
-        let mut r = CreateActivityRequest::new(agreement_id.to_owned());
+        let mut r = CreateActivityRequest::new(agreement_id.into());
         r.requestor_pub_key = Some(pub_key.to_string());
         self.client.post("activity").send_json(&r).json().await
     }
 
+    /// Creates an activity within an SGX enclave, attested according to `config`.
     #[cfg(feature = "sgx")]
     pub async fn create_secure_activity(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
+        config: &crate::SgxConfig,
     ) -> Result<sgx::SecureActivityRequestorApi> {
+        let agreement_id: AgreementId = agreement_id.into();
         let s = secp256k1::Secp256k1::new();
         let (secret, pub_key) = s.generate_keypair(&mut rand::thread_rng());
         let result = self
-            .create_secure_activity_raw(agreement_id, pub_key)
+            .create_secure_activity_raw(&agreement_id, pub_key)
             .await?;
         let api = sgx::SecureActivityRequestorApi::from_response(
             self.client.clone(),
             result.activity_id.clone(),
-            agreement_id,
+            &agreement_id,
             result,
             secret,
+            config,
         )
-        .await
-        .map_err(|e| crate::Error::InternalError(e.to_string()))?;
+        .await?;
         Ok(api)
     }
 
     /// Destroys given Activity.
-    pub async fn destroy_activity(&self, activity_id: &str) -> Result<()> {
+    pub async fn destroy_activity(&self, activity_id: impl Into<ActivityId>) -> Result<()> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}");
 
         // Specify serialization target because of a rustc bug falsely
@@ -79,7 +87,12 @@ impl ActivityRequestorControlApi {
     }
 
     /// Executes an ExeScript batch within a given Activity.
-    pub async fn exec(&self, script: ExeScriptRequest, activity_id: &str) -> Result<String> {
+    pub async fn exec(
+        &self,
+        script: ExeScriptRequest,
+        activity_id: impl Into<ActivityId>,
+    ) -> Result<String> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/exec");
         self.client.post(&uri).send_json(&script).json().await
     }
@@ -88,35 +101,46 @@ impl ActivityRequestorControlApi {
     #[rustfmt::skip]
     pub async fn get_exec_batch_results(
         &self,
-        activity_id: &str,
+        activity_id: impl Into<ActivityId>,
         batch_id: &str,
         timeout: Option<f32>,
         command_index: Option<usize>,
     ) -> Result<Vec<ExeScriptCommandResult>> {
+        let activity_id = activity_id.into();
         let uri = url_format!(
             "activity/{activity_id}/exec/{batch_id}",
             #[query] timeout,
             #[query] command_index,
         );
-        self.client.get(&uri).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&uri)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .json()
+            .await
+            .or_else(default_on_timeout)
     }
 
     /// Streams ExeScript batch results
     pub async fn stream_exec_batch_results(
         &self,
-        activity_id: &str,
+        activity_id: impl Into<ActivityId>,
         batch_id: &str,
     ) -> Result<impl Stream<Item = RuntimeEvent>> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/exec/{batch_id}",);
+        let unknown_events = self.client.unknown_events();
         let stream = self
             .client
             .event_stream(&uri)
             .await?
-            .filter_map(|result| async {
-                match result {
+            .filter_map(move |result| {
+                let event = match result {
                     Ok(evt) => RuntimeEvent::try_from(evt).ok(),
                     _ => None,
-                }
+                };
+                let event = event.filter(|event| unknown_events.keep(&uri, event));
+                async { event }
             });
         Ok(stream)
     }
@@ -137,8 +161,7 @@ pub mod sgx {
     use crate::model::activity::encrypted as enc;
     use crate::model::activity::{Credentials, ExeScriptCommand, SgxCredentials};
     use crate::Error as AppError;
-    use crate::SGX_CONFIG;
-    use graphene_sgx::AttestationResponse;
+    use crate::{SgxConfig, SgxVerifier};
     use hex;
     use secp256k1::{PublicKey, SecretKey};
     use std::sync::Arc;
@@ -151,8 +174,8 @@ pub mod sgx {
         MissingKeys,
         #[error("activity with unknown keys")]
         InvalidKeys,
-        #[error("{0}")]
-        AttestationFailed(String),
+        #[error("attestation failed: {0}")]
+        AttestationFailed(#[from] crate::SgxAttestationError),
         #[error("invalid credentials: {0}")]
         InvalidCredentials(String),
         #[error("invalid agreement")]
@@ -163,6 +186,15 @@ pub mod sgx {
         InternalError(String),
     }
 
+    impl From<SgxError> for AppError {
+        fn from(err: SgxError) -> Self {
+            match err {
+                SgxError::AttestationFailed(e) => AppError::Attestation(e),
+                err => AppError::InternalError(err.to_string()),
+            }
+        }
+    }
+
     macro_rules! map_error {
         ($($type:ty => $error:path)*) => {
             $(
@@ -182,7 +214,7 @@ pub mod sgx {
     }
 
     struct Session {
-        activity_id: String,
+        activity_id: ActivityId,
         #[allow(unused)]
         enclave_key: PublicKey,
         ctx: EncryptionCtx,
@@ -204,10 +236,11 @@ pub mod sgx {
     impl SecureActivityRequestorApi {
         pub async fn from_response(
             client: WebClient,
-            activity_id: String,
-            agreement_id: &str,
+            activity_id: ActivityId,
+            agreement_id: &AgreementId,
             response: CreateActivityResult,
             requestor_key: SecretKey,
+            config: &SgxConfig,
         ) -> std::result::Result<Self, SgxError> {
             let sgx: SgxCredentials = match response.credentials {
                 Some(Credentials::Sgx(sgx)) => sgx,
@@ -216,7 +249,7 @@ pub mod sgx {
             };
             let enclave_key = sgx.enclave_pub_key;
             let ctx = EncryptionCtx::new(&enclave_key, &requestor_key);
-            let nonce = &activity_id.to_owned();
+            let nonce = &activity_id.to_string();
             let session = Arc::new(Session {
                 activity_id,
                 enclave_key,
@@ -224,7 +257,7 @@ pub mod sgx {
                 proof: sgx.clone(),
             });
 
-            if SGX_CONFIG.enable_attestation {
+            if config.enable_attestation {
                 let agreement = WebClient::builder()
                     .auth_token(&std::env::var("YAGNA_APPKEY")?)
                     .build()
@@ -244,35 +277,15 @@ pub mod sgx {
                     .as_str()
                     .ok_or(SgxError::InvalidAgreement)?;
 
-                let evidence = AttestationResponse::new(sgx.ias_report.clone(), &sgx.ias_sig);
-                let mut verifier = evidence.verifier();
-                verifier = verifier
-                    .data(&sgx.requestor_pub_key.serialize())
-                    .data(&sgx.enclave_pub_key.serialize())
-                    .data(task_package.as_bytes())
-                    .mr_enclave_list(&SGX_CONFIG.exeunit_hashes)
+                let result = SgxVerifier::new(config)
                     .nonce(nonce)
-                    .max_age(SGX_CONFIG.max_evidence_age);
-
-                if !SGX_CONFIG.allow_debug {
-                    verifier = verifier.not_debug();
-                }
-
-                if !SGX_CONFIG.allow_outdated_tcb {
-                    verifier = verifier.not_outdated();
-                }
-
-                let attestation_result = verifier.check();
-                if attestation_result.is_ok() {
-                    log::info!("Attestation OK");
-                    Ok(SecureActivityRequestorApi { client, session })
-                } else {
-                    log::warn!("Attestation failed: {:?}", attestation_result);
-                    Err(SgxError::AttestationFailed(format!(
-                        "{:?}",
-                        attestation_result
-                    )))
+                    .verify_credentials(&sgx, task_package);
+                if let Err(e) = result {
+                    log::warn!("Attestation failed: {}", e);
+                    return Err(e.into());
                 }
+                log::info!("Attestation OK");
+                Ok(SecureActivityRequestorApi { client, session })
             } else {
                 log::info!("Attestation disabled");
                 Ok(SecureActivityRequestorApi { client, session })
@@ -283,7 +296,7 @@ pub mod sgx {
             Credentials::Sgx(self.session.proof.clone())
         }
 
-        pub fn activity_id(&self) -> String {
+        pub fn activity_id(&self) -> ActivityId {
             self.session.activity_id.clone()
         }
 
@@ -341,6 +354,7 @@ pub mod sgx {
         }
 
         async fn send(&self, request: enc::Request) -> Result<enc::Response> {
+            let server_timeout = timeout_secs(request.timeout);
             let bytes = self
                 .session
                 .ctx
@@ -353,7 +367,15 @@ pub mod sgx {
             let response = self
                 .session
                 .ctx
-                .decrypt(&self.client.post(&uri).send_bytes(bytes).bytes().await?)
+                .decrypt(
+                    &self
+                        .client
+                        .post(&uri)
+                        .long_poll(server_timeout)
+                        .send_bytes(bytes)
+                        .bytes()
+                        .await?,
+                )
                 .map_err(|e| AppError::InternalError(e.to_string()))?;
             Ok(response)
         }
@@ -382,4 +404,23 @@ mod test {
             .unwrap();
         assert_eq!(data2.as_slice(), data.as_ref())
     }
+
+    #[test]
+    #[cfg(feature = "sgx")]
+    fn attestation_errors() {
+        use super::sgx::SgxError;
+        use crate::{Error, SgxAttestationError};
+
+        let err = Error::from(SgxError::AttestationFailed(
+            SgxAttestationError::InvalidSignature,
+        ));
+        assert!(matches!(
+            err,
+            Error::Attestation(SgxAttestationError::InvalidSignature)
+        ));
+        assert!(matches!(
+            Error::from(SgxError::InvalidAgreement),
+            Error::InternalError(_)
+        ));
+    }
 }
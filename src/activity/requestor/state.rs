@@ -3,6 +3,8 @@ use ya_client_model::activity::{
     ActivityState, ActivityUsage, ExeScriptCommandState, ACTIVITY_API_PATH,
 };
 
+use ya_client_model::ActivityId;
+
 use crate::{web::WebClient, web::WebInterface, Result};
 
 /// Bindings for Requestor State part of the Activity API.
@@ -22,19 +24,25 @@ impl WebInterface for ActivityRequestorStateApi {
 
 impl ActivityRequestorStateApi {
     /// Get running command for a specified Activity.
-    pub async fn get_running_command(&self, activity_id: &str) -> Result<ExeScriptCommandState> {
+    pub async fn get_running_command(
+        &self,
+        activity_id: impl Into<ActivityId>,
+    ) -> Result<ExeScriptCommandState> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/command");
         self.client.get(&uri).send().json().await
     }
 
     /// Get state of specified Activity.
-    pub async fn get_state(&self, activity_id: &str) -> Result<ActivityState> {
+    pub async fn get_state(&self, activity_id: impl Into<ActivityId>) -> Result<ActivityState> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/state");
         self.client.get(&uri).send().json().await
     }
 
     /// Get usage of specified Activity.
-    pub async fn get_usage(&self, activity_id: &str) -> Result<ActivityUsage> {
+    pub async fn get_usage(&self, activity_id: impl Into<ActivityId>) -> Result<ActivityUsage> {
+        let activity_id = activity_id.into();
         let uri = url_format!("activity/{activity_id}/usage");
         self.client.get(&uri).send().json().await
     }
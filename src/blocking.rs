@@ -0,0 +1,236 @@
+//! Blocking bindings of the Yagna API
+//!
+//! Wraps the async APIs so that they can be used from synchronous code.
+//! Requests are run on a single-threaded runtime owned by the [`WebClient`],
+//! which is shared with all APIs obtained from it.
+//!
+//! Methods must not be called from within an async runtime, as they block
+//! the current thread until the request completes.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use ya_client::blocking::{payment::PaymentApi, WebClient};
+//!
+//! fn main() -> ya_client::Result<()> {
+//!     let client = WebClient::with_token("app-key");
+//!     let api: PaymentApi = client.interface()?;
+//!     for invoice in api.get_invoices_iter::<chrono::Utc>(None, None) {
+//!         println!("{}", invoice?.invoice_id);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use actix_rt::{System, SystemRunner};
+use futures::stream::LocalBoxStream;
+use futures::{Future, Stream, StreamExt};
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+use url::Url;
+
+use crate::readiness::ReadinessReport;
+use crate::web::{self, CancellationToken, WebClientBuilder, WebInterface};
+use crate::Result;
+
+/// Generates blocking counterparts of the async API methods,
+/// documented with a link to the async one.
+///
+/// Methods generic over `Tz` get the same `TimeZone` bounds as the async ones.
+macro_rules! blocking_impl {
+    ($api:path {
+        $(
+            $(#[$meta:meta])*
+            fn $name:ident $(<$tz:ident>)? (&self $(, $arg:ident : $ty:ty)* $(,)?) -> $ret:ty;
+        )*
+    }) => {
+        impl $crate::blocking::Blocking<$api> {
+            $(
+                #[doc = concat!("Blocking version of [`", stringify!($name), "`](", stringify!($api), "::", stringify!($name), ").")]
+                $(#[$meta])*
+                pub fn $name $(<$tz>)? (&self $(, $arg: $ty)*) -> $crate::Result<$ret>
+                $(where
+                    $tz: chrono::TimeZone,
+                    $tz::Offset: std::fmt::Display,)?
+                {
+                    self.block_on(self.api.$name($($arg),*))
+                }
+            )*
+        }
+    };
+}
+
+pub mod activity;
+pub mod identity;
+pub mod market;
+pub mod net;
+pub mod payment;
+
+/// Runtime running the requests of a blocking client.
+#[derive(Clone)]
+struct Runtime(Rc<SystemRunner>);
+
+impl Runtime {
+    fn new() -> Self {
+        Runtime(Rc::new(System::new()))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+/// Blocking counterpart of [`web::WebClient`].
+#[derive(Clone)]
+pub struct WebClient {
+    client: web::WebClient,
+    rt: Runtime,
+}
+
+impl WebClient {
+    pub fn builder() -> WebClientBuilder {
+        web::WebClient::builder()
+    }
+
+    pub fn with_token(token: &str) -> WebClient {
+        Self::new(WebClientBuilder::default().auth_token(token))
+    }
+
+    /// Builds the client together with the runtime running its requests.
+    pub fn new(builder: WebClientBuilder) -> WebClient {
+        let rt = Runtime::new();
+        let client = rt.block_on(async move { builder.build() });
+        WebClient { client, rt }
+    }
+
+    /// See [`web::WebClient::with_timeout`].
+    pub fn with_timeout(&self, timeout: Duration) -> WebClient {
+        WebClient {
+            client: self.client.with_timeout(timeout),
+            rt: self.rt.clone(),
+        }
+    }
+
+    /// See [`web::WebClient::with_cancellation`].
+    ///
+    /// The token may be cancelled from another thread to abort a blocked call.
+    pub fn with_cancellation(&self, cancel: CancellationToken) -> WebClient {
+        WebClient {
+            client: self.client.with_cancellation(cancel),
+            rt: self.rt.clone(),
+        }
+    }
+
+    /// Async client used by this one.
+    pub fn inner(&self) -> &web::WebClient {
+        &self.client
+    }
+
+    pub fn interface<T: WebInterface>(&self) -> Result<Blocking<T>> {
+        self.interface_at(None)
+    }
+
+    pub fn interface_at<T: WebInterface>(
+        &self,
+        base_url: impl Into<Option<Url>>,
+    ) -> Result<Blocking<T>> {
+        Ok(Blocking {
+            api: self.client.interface_at(base_url)?,
+            rt: self.rt.clone(),
+        })
+    }
+
+    /// See [`web::WebClient::wait_ready`].
+    pub fn wait_ready(&self, timeout: Duration) -> ReadinessReport {
+        self.rt.block_on(self.client.wait_ready(timeout))
+    }
+}
+
+impl fmt::Debug for WebClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebClient").finish_non_exhaustive()
+    }
+}
+
+/// Blocking wrapper of an async API.
+///
+/// Use the type aliases from the submodules, eg. [`payment::PaymentApi`].
+#[derive(Clone)]
+pub struct Blocking<T> {
+    api: T,
+    rt: Runtime,
+}
+
+impl<T> Blocking<T> {
+    /// Async API wrapped by this one.
+    pub fn inner(&self) -> &T {
+        &self.api
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.rt.block_on(future)
+    }
+
+    fn wrap<U>(&self, api: U) -> Blocking<U> {
+        Blocking {
+            api,
+            rt: self.rt.clone(),
+        }
+    }
+
+    fn iter<S: Stream + 'static>(&self, stream: S) -> Iter<S::Item> {
+        Iter {
+            stream: stream.boxed_local(),
+            rt: self.rt.clone(),
+        }
+    }
+}
+
+/// Blocking iterator over an async stream.
+pub struct Iter<T> {
+    stream: LocalBoxStream<'static, T>,
+    rt: Runtime,
+}
+
+impl<T> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rt.block_on(self.stream.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn unreachable_client() -> WebClient {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        WebClient::new(
+            WebClient::builder().api_url(format!("http://127.0.0.1:{}", port).parse().unwrap()),
+        )
+    }
+
+    #[test]
+    fn request_outside_runtime() {
+        let client = unreachable_client();
+        let api: identity::IdentityApi = client.interface().unwrap();
+        assert!(matches!(api.me(), Err(Error::SendRequestError { .. })));
+    }
+
+    #[test]
+    fn stream_as_iterator() {
+        let client = unreachable_client();
+        let api: payment::PaymentApi = client.interface().unwrap();
+        let invoices = api
+            .get_invoices_iter::<chrono::Utc>(None, None)
+            .collect::<Vec<_>>();
+        assert_eq!(invoices.len(), 1);
+        assert!(invoices[0].is_err());
+    }
+}
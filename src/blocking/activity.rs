@@ -0,0 +1,78 @@
+//! Blocking bindings of the Activity API
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use ya_client_model::activity::{
+    ActivityState, ActivityUsage, ExeScriptCommandResult, ExeScriptCommandState, ExeScriptRequest,
+    ProviderEvent, RuntimeEvent,
+};
+
+use ya_client_model::{ActivityId, AgreementId};
+
+use super::{Blocking, Iter};
+use crate::Result;
+
+pub type ActivityProviderApi = Blocking<crate::activity::ActivityProviderApi>;
+pub type ActivityRequestorApi = Blocking<crate::activity::ActivityRequestorApi>;
+pub type ActivityRequestorControlApi = Blocking<crate::activity::ActivityRequestorControlApi>;
+pub type ActivityRequestorStateApi = Blocking<crate::activity::ActivityRequestorStateApi>;
+
+blocking_impl!(crate::activity::ActivityProviderApi {
+    fn get_activity_ids(&self) -> Vec<ActivityId>;
+    fn get_activity_state(&self, activity_id: impl Into<ActivityId>) -> ActivityState;
+    fn set_activity_state(&self, activity_id: impl Into<ActivityId>, state: &ActivityState) -> ();
+    fn get_activity_usage(&self, activity_id: impl Into<ActivityId>) -> ActivityUsage;
+    fn get_activity_agreement(&self, activity_id: impl Into<ActivityId>) -> AgreementId;
+    fn get_activity_events(
+        &self,
+        after_timestamp: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+    ) -> Vec<ProviderEvent>;
+});
+
+impl ActivityRequestorApi {
+    pub fn control(&self) -> ActivityRequestorControlApi {
+        self.wrap(self.api.control().clone())
+    }
+
+    pub fn state(&self) -> ActivityRequestorStateApi {
+        self.wrap(self.api.state().clone())
+    }
+}
+
+blocking_impl!(crate::activity::ActivityRequestorApi {
+    fn get_agreement(&self, activity_id: impl Into<ActivityId>) -> AgreementId;
+});
+
+blocking_impl!(crate::activity::ActivityRequestorControlApi {
+    fn create_activity(&self, agreement_id: impl Into<AgreementId>) -> ActivityId;
+    fn destroy_activity(&self, activity_id: impl Into<ActivityId>) -> ();
+    fn exec(&self, script: ExeScriptRequest, activity_id: impl Into<ActivityId>) -> String;
+    fn get_exec_batch_results(
+        &self,
+        activity_id: impl Into<ActivityId>,
+        batch_id: &str,
+        timeout: Option<f32>,
+        command_index: Option<usize>,
+    ) -> Vec<ExeScriptCommandResult>;
+});
+
+impl ActivityRequestorControlApi {
+    /// Blocking version of [`stream_exec_batch_results`](crate::activity::ActivityRequestorControlApi::stream_exec_batch_results).
+    pub fn stream_exec_batch_results(
+        &self,
+        activity_id: impl Into<ActivityId>,
+        batch_id: &str,
+    ) -> Result<Iter<RuntimeEvent>> {
+        let activity_id: ActivityId = activity_id.into();
+        let stream = self.block_on(self.api.stream_exec_batch_results(activity_id, batch_id))?;
+        Ok(self.iter(stream))
+    }
+}
+
+blocking_impl!(crate::activity::ActivityRequestorStateApi {
+    fn get_running_command(&self, activity_id: impl Into<ActivityId>) -> ExeScriptCommandState;
+    fn get_state(&self, activity_id: impl Into<ActivityId>) -> ActivityState;
+    fn get_usage(&self, activity_id: impl Into<ActivityId>) -> ActivityUsage;
+});
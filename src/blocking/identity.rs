@@ -0,0 +1,11 @@
+//! Blocking bindings of the Identity API
+use ya_client_model::identity::Identity;
+
+use super::Blocking;
+
+pub type IdentityApi = Blocking<crate::identity::IdentityApi>;
+
+blocking_impl!(crate::identity::IdentityApi {
+    fn me(&self) -> Identity;
+    fn identity_of(&self, app_key: &str) -> Identity;
+});
@@ -0,0 +1,151 @@
+//! Blocking bindings of the Market API
+use chrono::{DateTime, Utc};
+use ya_client_model::market::scan::NewScan;
+use ya_client_model::market::{
+    agreement::State, Agreement, AgreementListEntry, AgreementOperationEvent, AgreementProposal,
+    AgreementTerminationReason, Demand, NewDemand, NewOffer, NewProposal, Offer, Proposal,
+    ProviderEvent, Reason, RequestorEvent,
+};
+use ya_client_model::{AgreementId, NodeId, SubscriptionId};
+
+use super::{Blocking, Iter};
+use crate::Result;
+
+pub type MarketProviderApi = Blocking<crate::market::MarketProviderApi>;
+pub type MarketRequestorApi = Blocking<crate::market::MarketRequestorApi>;
+
+blocking_impl!(crate::market::MarketProviderApi {
+    fn subscribe(&self, offer: &NewOffer) -> SubscriptionId;
+    fn get_offers(&self) -> Vec<Offer>;
+    fn unsubscribe(&self, subscription_id: impl Into<SubscriptionId>) -> ();
+    fn collect(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        timeout: Option<f32>,
+        max_events: Option<i32>,
+    ) -> Vec<ProviderEvent>;
+    fn get_proposal(&self, subscription_id: impl Into<SubscriptionId>, proposal_id: &str) -> Proposal;
+    fn reject_proposal(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+        reason: &Option<Reason>,
+    ) -> ();
+    fn counter_proposal(
+        &self,
+        offer_proposal: &NewProposal,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+    ) -> String;
+    fn approve_agreement(
+        &self,
+        agreement_id: impl Into<AgreementId>,
+        app_session_id: Option<String>,
+        timeout: Option<f32>,
+    ) -> ();
+    fn reject_agreement(&self, agreement_id: impl Into<AgreementId>, reason: &Option<Reason>) -> ();
+    fn terminate_agreement(&self, agreement_id: impl Into<AgreementId>, reason: &Option<Reason>) -> ();
+    fn list_agreements(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> Vec<AgreementListEntry>;
+    fn get_agreement(&self, agreement_id: impl Into<AgreementId>) -> Agreement;
+    fn get_agreement_termination_reason(&self, agreement_id: impl Into<AgreementId>) -> AgreementTerminationReason;
+    fn collect_agreement_events<Tz>(
+        &self,
+        timeout: Option<f32>,
+        after_timestamp: Option<&DateTime<Tz>>,
+        max_events: Option<i32>,
+        app_session_id: Option<String>,
+    ) -> Vec<AgreementOperationEvent>;
+});
+
+impl MarketProviderApi {
+    /// Blocking version of [`list_agreements_stream`](crate::market::MarketProviderApi::list_agreements_stream).
+    pub fn list_agreements_iter(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> Iter<Result<AgreementListEntry>> {
+        self.iter(
+            self.api
+                .list_agreements_stream(state, before_date, after_date, app_session_id),
+        )
+    }
+}
+
+blocking_impl!(crate::market::MarketRequestorApi {
+    fn subscribe(&self, demand: &NewDemand) -> SubscriptionId;
+    fn get_demands(&self) -> Vec<Demand>;
+    fn unsubscribe(&self, subscription_id: impl Into<SubscriptionId>) -> ();
+    fn collect(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        timeout: Option<f32>,
+        max_events: Option<i32>,
+    ) -> Vec<RequestorEvent>;
+    fn counter_proposal(
+        &self,
+        demand_proposal: &NewProposal,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+    ) -> String;
+    fn get_proposal(&self, subscription_id: impl Into<SubscriptionId>, proposal_id: &str) -> Proposal;
+    fn reject_proposal(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+        reason: &Option<Reason>,
+    ) -> ();
+    fn create_agreement(&self, agreement: &AgreementProposal) -> AgreementId;
+    fn list_agreements(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> Vec<AgreementListEntry>;
+    fn get_agreement(&self, agreement_id: impl Into<AgreementId>) -> Agreement;
+    fn confirm_agreement(&self, agreement_id: impl Into<AgreementId>, app_session_id: Option<String>) -> ();
+    fn wait_for_approval(&self, agreement_id: impl Into<AgreementId>, timeout: Option<f32>) -> ();
+    fn cancel_agreement(&self, agreement_id: impl Into<AgreementId>, reason: &Option<Reason>) -> ();
+    fn terminate_agreement(&self, agreement_id: impl Into<AgreementId>, reason: &Option<Reason>) -> ();
+    fn get_agreement_termination_reason(&self, agreement_id: impl Into<AgreementId>) -> AgreementTerminationReason;
+    fn collect_agreement_events<Tz>(
+        &self,
+        timeout: Option<f32>,
+        after_timestamp: Option<&DateTime<Tz>>,
+        max_events: Option<i32>,
+        app_session_id: Option<String>,
+    ) -> Vec<AgreementOperationEvent>;
+    fn begin_scan(&self, scan_req: &NewScan) -> SubscriptionId;
+    fn collect_scan(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        timeout: Option<f32>,
+        max_events: Option<usize>,
+        peer_id: Option<&NodeId>,
+    ) -> Vec<Offer>;
+    fn end_scan(&self, subscription_id: impl Into<SubscriptionId>) -> ();
+});
+
+impl MarketRequestorApi {
+    /// Blocking version of [`list_agreements_stream`](crate::market::MarketRequestorApi::list_agreements_stream).
+    pub fn list_agreements_iter(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> Iter<Result<AgreementListEntry>> {
+        self.iter(
+            self.api
+                .list_agreements_stream(state, before_date, after_date, app_session_id),
+        )
+    }
+}
@@ -0,0 +1,29 @@
+//! Blocking bindings of the Net API
+//!
+//! [`NetVpnApi::connect_tcp`](crate::net::NetVpnApi::connect_tcp) and
+//! [`NetVpnApi::connect_tcp_stream`](crate::net::NetVpnApi::connect_tcp_stream) have no blocking
+//! counterparts, as the returned connections can only be used asynchronously.
+use crate::model::net::*;
+
+use super::Blocking;
+
+pub type NetApi = Blocking<crate::net::NetApi>;
+pub type NetVpnApi = Blocking<crate::net::NetVpnApi>;
+
+blocking_impl!(crate::net::NetApi {
+    fn get_status(&self) -> Status;
+});
+
+blocking_impl!(crate::net::NetVpnApi {
+    fn version(&self) -> crate::net::NetApiVersion;
+    fn get_networks(&self) -> Vec<Network>;
+    fn create_network(&self, network: &NewNetwork) -> Network;
+    fn get_network(&self, network_id: &str) -> Network;
+    fn remove_network(&self, network_id: &str) -> ();
+    fn get_addresses(&self, network_id: &str) -> Vec<Address>;
+    fn add_address(&self, network_id: &str, address: &Address) -> ();
+    fn get_nodes(&self, network_id: &str) -> Vec<Node>;
+    fn add_node(&self, network_id: &str, node: &Node) -> ();
+    fn remove_node(&self, network_id: &str, node_id: &str) -> ();
+    fn list_tcp(&self, network_id: &str) -> Vec<Connection>;
+});
@@ -0,0 +1,171 @@
+//! Blocking bindings of the Payment API
+use chrono::{DateTime, TimeZone};
+use std::fmt::Display;
+use std::time::Duration;
+use ya_client_model::payment::payment::Signed;
+use ya_client_model::payment::*;
+use ya_client_model::{AllocationId, DebitNoteId, InvoiceId};
+
+use super::{Blocking, Iter};
+use crate::Result;
+
+pub type PaymentApi = Blocking<crate::payment::PaymentApi>;
+
+blocking_impl!(crate::payment::PaymentApi {
+    fn get_requestor_accounts(&self) -> Vec<Account>;
+    fn get_provider_accounts(&self) -> Vec<Account>;
+    fn create_allocation(&self, allocation: &NewAllocation) -> Allocation;
+    fn get_allocations<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        max_items: Option<u32>,
+    ) -> Vec<Allocation>;
+    fn get_allocation(&self, allocation_id: impl Into<AllocationId>) -> Allocation;
+    fn amend_allocation(&self, allocation_id: impl Into<AllocationId>, allocation: &AllocationUpdate) -> Allocation;
+    fn release_allocation(&self, allocation_id: impl Into<AllocationId>) -> ();
+    fn get_demand_decorations(&self, allocation_ids: Vec<String>) -> MarketDecoration;
+    fn get_debit_notes<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        max_items: Option<u32>,
+    ) -> Vec<DebitNote>;
+    fn get_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> DebitNote;
+    fn get_payments_for_debit_note<Tz>(
+        &self,
+        debit_note_id: impl Into<DebitNoteId>,
+        after_timestamp: Option<DateTime<Tz>>,
+        max_items: Option<u32>,
+    ) -> Vec<Payment>;
+    fn get_debit_note_events<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Vec<DebitNoteEvent>;
+    fn issue_debit_note(&self, debit_note: &NewDebitNote) -> DebitNote;
+    fn send_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> ();
+    fn cancel_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> ();
+    fn accept_debit_note(&self, debit_note_id: impl Into<DebitNoteId>, acceptance: &Acceptance) -> ();
+    fn reject_debit_note(&self, debit_note_id: impl Into<DebitNoteId>, rejection: &Rejection) -> ();
+    fn get_invoices<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        max_items: Option<u32>,
+    ) -> Vec<Invoice>;
+    fn get_invoice(&self, invoice_id: impl Into<InvoiceId>) -> Invoice;
+    fn get_payments_for_invoice<Tz>(
+        &self,
+        invoice_id: impl Into<InvoiceId>,
+        after_timestamp: Option<DateTime<Tz>>,
+        max_items: Option<u32>,
+    ) -> Vec<Payment>;
+    fn get_invoice_events<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Vec<InvoiceEvent>;
+    fn issue_invoice(&self, invoice: &NewInvoice) -> Invoice;
+    fn send_invoice(&self, invoice_id: impl Into<InvoiceId>) -> ();
+    fn cancel_invoice(&self, invoice_id: impl Into<InvoiceId>) -> ();
+    fn accept_invoice(&self, invoice_id: impl Into<InvoiceId>, acceptance: &Acceptance) -> ();
+    fn reject_invoice(&self, invoice_id: impl Into<InvoiceId>, rejection: &Rejection) -> ();
+    fn get_payments<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Vec<Payment>;
+    fn get_signed_payments<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Vec<Signed<Payment>>;
+    fn get_payment(&self, payment_id: &str) -> Payment;
+    fn get_signed_payment(&self, payment_id: &str) -> Signed<Payment>;
+    fn driver_status(
+        &self,
+        driver: Option<String>,
+        network: Option<String>,
+    ) -> Vec<DriverStatusProperty>;
+});
+
+impl PaymentApi {
+    /// Blocking version of [`get_allocations_stream`](crate::payment::PaymentApi::get_allocations_stream).
+    pub fn get_allocations_iter<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> Iter<Result<Allocation>>
+    where
+        Tz: TimeZone + 'static,
+        Tz::Offset: Display,
+    {
+        self.iter(self.api.get_allocations_stream(after_timestamp, page_size))
+    }
+
+    /// Blocking version of [`get_debit_notes_stream`](crate::payment::PaymentApi::get_debit_notes_stream).
+    pub fn get_debit_notes_iter<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> Iter<Result<DebitNote>>
+    where
+        Tz: TimeZone + 'static,
+        Tz::Offset: Display,
+    {
+        self.iter(self.api.get_debit_notes_stream(after_timestamp, page_size))
+    }
+
+    /// Blocking version of [`get_invoices_stream`](crate::payment::PaymentApi::get_invoices_stream).
+    pub fn get_invoices_iter<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> Iter<Result<Invoice>>
+    where
+        Tz: TimeZone + 'static,
+        Tz::Offset: Display,
+    {
+        self.iter(self.api.get_invoices_stream(after_timestamp, page_size))
+    }
+
+    /// Blocking version of [`get_payments_stream`](crate::payment::PaymentApi::get_payments_stream).
+    pub fn get_payments_iter<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        page_size: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Iter<Result<Payment>>
+    where
+        Tz: TimeZone + 'static,
+        Tz::Offset: Display,
+    {
+        self.iter(
+            self.api
+                .get_payments_stream(after_timestamp, page_size, app_session_id),
+        )
+    }
+
+    /// Blocking version of [`get_signed_payments_stream`](crate::payment::PaymentApi::get_signed_payments_stream).
+    pub fn get_signed_payments_iter<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        page_size: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Iter<Result<Signed<Payment>>>
+    where
+        Tz: TimeZone + 'static,
+        Tz::Offset: Display,
+    {
+        self.iter(
+            self.api
+                .get_signed_payments_stream(after_timestamp, page_size, app_session_id),
+        )
+    }
+}
@@ -41,6 +41,8 @@ pub enum Error {
     InvalidHeaderName(#[from] awc::http::header::InvalidHeaderName),
     #[error("Invalid header: {0}")]
     InvalidHeaderValue(#[from] awc::http::header::InvalidHeaderValue),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
     #[error("Invalid UTF8 string: {0}")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
     #[error("Invalid UTF8 string: {0}")]
@@ -53,6 +55,17 @@ pub enum Error {
     InternalError(String),
     #[error("Event stream error: {0}")]
     EventStreamError(String),
+    #[error("Invalid rate limit: {0} requests per second")]
+    InvalidRateLimit(f64),
+    #[error("Circuit breaker open, not requesting {method} {url}")]
+    CircuitOpen { method: Method, url: String },
+    #[error("Request cancelled: {method} {url}")]
+    Cancelled { method: Method, url: String },
+    #[error("No free address left in network {network_id}")]
+    NoFreeAddress { network_id: String },
+    #[cfg(feature = "sgx")]
+    #[error("SGX attestation failed: {0}")]
+    Attestation(#[from] crate::SgxAttestationError),
 }
 
 impl From<PayloadError> for Error {
@@ -1,4 +1,5 @@
 use ya_client_model::identity::Identity;
+use ya_client_model::NodeId;
 
 use crate::web::{WebClient, WebInterface};
 use crate::Result;
@@ -21,7 +22,189 @@ impl WebInterface for IdentityApi {
 }
 
 impl IdentityApi {
+    /// Identity the client acts as, determined by its app key.
     pub async fn me(&self) -> Result<Identity> {
         self.client.get("me").send().json().await
     }
+
+    /// Identity the given app key is tied to.
+    pub async fn identity_of(&self, app_key: &str) -> Result<Identity> {
+        IdentityApi::from_client(self.client.with_app_key(app_key))
+            .me()
+            .await
+    }
+}
+
+/// Several identities of a single daemon, each with a client acting as it.
+///
+/// The daemon ties every app key to a single identity, so the identities are
+/// resolved from the app keys of the operator.
+pub struct Identities {
+    entries: Vec<(Identity, WebClient)>,
+}
+
+impl Identities {
+    /// Resolves the identity of each app key, keeping the first key of every identity.
+    pub async fn resolve(
+        client: &WebClient,
+        app_keys: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self> {
+        let api: IdentityApi = client.interface()?;
+        let mut entries: Vec<(Identity, WebClient)> = Vec::new();
+        for app_key in app_keys {
+            let app_key = app_key.as_ref();
+            let identity = api.identity_of(app_key).await?;
+            if entries.iter().all(|(i, _)| i.identity != identity.identity) {
+                entries.push((identity, client.with_app_key(app_key)));
+            }
+        }
+        Ok(Identities { entries })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Identity> {
+        self.entries.iter().map(|(identity, _)| identity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<&Identity> {
+        self.entry(node_id).map(|(identity, _)| identity)
+    }
+
+    /// Role of the app key of the identity, which determines its permissions.
+    pub fn role(&self, node_id: &NodeId) -> Option<&str> {
+        self.get(node_id).map(|identity| identity.role.as_str())
+    }
+
+    /// Client acting as the identity.
+    pub fn client(&self, node_id: &NodeId) -> Option<&WebClient> {
+        self.entry(node_id).map(|(_, client)| client)
+    }
+
+    /// API acting as the identity.
+    pub fn interface<T: WebInterface>(&self, node_id: &NodeId) -> Option<Result<T>> {
+        self.client(node_id).map(WebClient::interface)
+    }
+
+    fn entry(&self, node_id: &NodeId) -> Option<&(Identity, WebClient)> {
+        self.entries
+            .iter()
+            .find(|(identity, _)| identity.identity == *node_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Request, Response};
+    use crate::Error;
+    use std::thread::JoinHandle;
+
+    const KEYS: [(&str, &str, &str); 2] = [
+        (
+            "key-a",
+            "0x0000000000000000000000000000000000000001",
+            "manager",
+        ),
+        (
+            "key-b",
+            "0x0000000000000000000000000000000000000002",
+            "requestor",
+        ),
+    ];
+
+    fn app_key(request: &Request) -> &str {
+        request
+            .header("authorization")
+            .unwrap_or_default()
+            .trim_start_matches("Bearer ")
+    }
+
+    /// Serves `count` requests of a daemon with [`KEYS`].
+    fn serve(count: usize) -> (WebClient, JoinHandle<Vec<Request>>) {
+        let (url, handle) = testing::serve(count, |request| {
+            match KEYS.iter().find(|(key, ..)| *key == app_key(request)) {
+                Some((_, node_id, role)) => Response::json(format!(
+                    r#"{{"identity":"{node_id}","name":"{role}","role":"{role}"}}"#
+                )),
+                None => Response::status("401 Unauthorized"),
+            }
+        });
+        let client = WebClient::builder()
+            .api_url(url.parse().unwrap())
+            .auth_token("key-a")
+            .build();
+        (client, handle)
+    }
+
+    fn node_id(n: usize) -> NodeId {
+        KEYS[n].1.parse().unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn app_key_overrides_token() {
+        let (client, server) = serve(2);
+        let api: IdentityApi = client.interface().unwrap();
+        assert_eq!(api.me().await.unwrap().identity, node_id(0));
+        assert_eq!(api.identity_of("key-b").await.unwrap().identity, node_id(1));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].line, "GET /me HTTP/1.1");
+        assert_eq!(app_key(&requests[0]), "key-a");
+        assert_eq!(requests[1].line, "GET /me HTTP/1.1");
+        assert_eq!(app_key(&requests[1]), "key-b");
+    }
+
+    #[actix_rt::test]
+    async fn resolves_identities() {
+        let (client, server) = serve(3);
+        let identities = Identities::resolve(&client, ["key-a", "key-b", "key-a"])
+            .await
+            .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(identities.len(), 2);
+        let ids: Vec<_> = identities.iter().map(|i| i.identity).collect();
+        assert_eq!(ids, [node_id(0), node_id(1)]);
+        assert_eq!(identities.role(&node_id(0)), Some("manager"));
+        assert_eq!(identities.role(&node_id(1)), Some("requestor"));
+        assert!(identities.get(&NodeId::default()).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn clients_act_as_identities() {
+        let (client, server) = serve(4);
+        let identities = Identities::resolve(&client, ["key-b", "key-a"])
+            .await
+            .unwrap();
+
+        for n in [0, 1] {
+            let api: IdentityApi = identities.interface(&node_id(n)).unwrap().unwrap();
+            assert_eq!(api.me().await.unwrap().identity, node_id(n));
+        }
+        assert!(identities
+            .interface::<IdentityApi>(&NodeId::default())
+            .is_none());
+
+        let requests = server.join().unwrap();
+        let keys: Vec<_> = requests.iter().map(app_key).collect();
+        assert_eq!(keys, ["key-b", "key-a", "key-a", "key-b"]);
+    }
+
+    #[actix_rt::test]
+    async fn unknown_app_key() {
+        let (client, server) = serve(2);
+        let result = Identities::resolve(&client, ["key-a", "key-c"]).await;
+        assert!(matches!(
+            result,
+            Err(Error::HttpError { code, .. }) if code.as_u16() == 401
+        ));
+        server.join().unwrap();
+    }
 }
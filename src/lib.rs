@@ -8,17 +8,28 @@ pub mod identity;
 pub mod market;
 pub mod net;
 pub mod payment;
+pub mod readiness;
+pub mod version;
+
+mod paging;
+#[cfg(test)]
+mod testing;
 
 pub mod error;
 pub use error::Error;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 #[cfg(feature = "cli")]
 pub mod cli;
 
 #[cfg(feature = "sgx")]
 mod sgx;
 #[cfg(feature = "sgx")]
-pub use sgx::SGX_CONFIG;
+pub use sgx::{
+    SgxAttestationError, SgxConfig, SgxConfigBuilder, SgxConfigError, SgxVerifier,
+    SGX_CONFIG_ENV_VAR,
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -5,9 +5,14 @@ use ya_client_model::market::{
     MARKET_API_PATH,
 };
 
-use crate::{web::default_on_timeout, web::WebClient, web::WebInterface, Result};
+use crate::{
+    paging::single_page, web::default_on_timeout, web::timeout_secs, web::WebClient,
+    web::WebInterface, Result,
+};
 use chrono::{DateTime, TimeZone, Utc};
+use futures::Stream;
 use std::fmt::Display;
+use ya_client_model::{AgreementId, SubscriptionId};
 
 /// Bindings for Provider part of the Market API.
 #[derive(Clone)]
@@ -27,7 +32,7 @@ impl WebInterface for MarketProviderApi {
 impl MarketProviderApi {
     /// Publish Provider’s service capabilities (`Offer`) on the market to declare an
     /// interest in Demands meeting specified criteria.
-    pub async fn subscribe(&self, offer: &NewOffer) -> Result<String> {
+    pub async fn subscribe(&self, offer: &NewOffer) -> Result<SubscriptionId> {
         self.client.post("offers").send_json(&offer).json().await
     }
 
@@ -42,7 +47,8 @@ impl MarketProviderApi {
     /// **Note**: this will terminate all pending `collect_demands` calls on this subscription.
     /// This implies, that client code should not `unsubscribe_offer` before it has received
     /// all expected/useful inputs from `collect_demands`.
-    pub async fn unsubscribe(&self, subscription_id: &str) -> Result<()> {
+    pub async fn unsubscribe(&self, subscription_id: impl Into<SubscriptionId>) -> Result<()> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("offers/{subscription_id}");
         self.client.delete(&url).send().json().await
     }
@@ -57,7 +63,7 @@ impl MarketProviderApi {
     /// Returns Proposal related events:
     ///
     /// * `ProposalEvent` - Indicates that there is new Demand Proposal for
-    /// this Offer.
+    ///   this Offer.
     ///
     /// * `ProposalRejectedEvent` - Indicates that the Requestor has rejected
     ///   our previous Proposal related to this Offer. This effectively ends a
@@ -79,21 +85,33 @@ impl MarketProviderApi {
     #[rustfmt::skip]
     pub async fn collect(
         &self,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         timeout: Option<f32>,
         max_events: Option<i32>,
     ) -> Result<Vec<ProviderEvent>> {
+        let subscription_id = subscription_id.into();
         let url = url_format!(
             "offers/{subscription_id}/events",
             #[query] timeout,
             #[query] max_events,
         );
 
-        self.client.get(&url).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .events()
+            .await
+            .or_else(default_on_timeout)
     }
 
     /// Fetches Proposal (Demand) with given id.
-    pub async fn get_proposal(&self, subscription_id: &str, proposal_id: &str) -> Result<Proposal> {
+    pub async fn get_proposal(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+    ) -> Result<Proposal> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("offers/{subscription_id}/proposals/{proposal_id}",);
         self.client.get(&url).send().json().await
     }
@@ -104,10 +122,11 @@ impl MarketProviderApi {
     /// the sender will not create another counter-Proposal.
     pub async fn reject_proposal(
         &self,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         proposal_id: &str,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("offers/{subscription_id}/proposals/{proposal_id}/reject",);
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -119,9 +138,10 @@ impl MarketProviderApi {
     pub async fn counter_proposal(
         &self,
         offer_proposal: &NewProposal,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         proposal_id: &str,
     ) -> Result<String> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("offers/{subscription_id}/proposals/{proposal_id}",);
         self.client
             .post(&url)
@@ -139,15 +159,16 @@ impl MarketProviderApi {
     /// It returns one of the following options:
     ///
     /// * `Ok` Agreement approved. Indicates that the approved Agreement has been
-    ///  successfully delivered to the Requestor and acknowledged.
+    ///   successfully delivered to the Requestor and acknowledged.
     ///    - The Requestor side has been notified about the Provider’s commitment.
     ///    - The Provider is now ready to accept a request to start an Activity.
     ///    - The Requestor’s corresponding `wait_for_approval` call returns `Ok`
-    ///    (Approved) **after** this endpoint on the Provider side.
+    ///      (Approved) **after** this endpoint on the Provider side.
     ///
     /// * `Err` - Indicates that Agreement is not approved.
     ///   - `408` Agreement not approved within given timeout. Try again.
     ///   - `410` Agreement approval failed permanently.
+    ///
     /// Attached `ErrorMessage` contains further details:
     ///   - `Rejected` - Indicates that the Provider himself has already
     ///     called `reject_agreement`.
@@ -161,16 +182,22 @@ impl MarketProviderApi {
     #[rustfmt::skip]
     pub async fn approve_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         app_session_id: Option<String>,
         timeout: Option<f32>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!(
             "agreements/{agreement_id}/approve",
             #[query] app_session_id,
             #[query] timeout,
         );
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .json()
+            .await
     }
 
     /// Rejects Agreement proposed by the Requestor.
@@ -181,9 +208,10 @@ impl MarketProviderApi {
     /// **Note**: Mutually exclusive with `approve_agreement`.
     pub async fn reject_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/reject");
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -191,9 +219,10 @@ impl MarketProviderApi {
     /// Terminates approved Agreement.
     pub async fn terminate_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/terminate");
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -225,8 +254,27 @@ impl MarketProviderApi {
         self.client.get(&url).send().json().await
     }
 
+    /// Streams agreements matching given filters.
+    ///
+    /// The endpoint does not limit the number of returned entries,
+    /// so all of them are fetched with a single request.
+    pub fn list_agreements_stream(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> impl Stream<Item = Result<AgreementListEntry>> {
+        let api = self.clone();
+        single_page(async move {
+            api.list_agreements(state, before_date, after_date, app_session_id)
+                .await
+        })
+    }
+
     /// Fetches agreement with given agreement id.
-    pub async fn get_agreement(&self, agreement_id: &str) -> Result<Agreement> {
+    pub async fn get_agreement(&self, agreement_id: impl Into<AgreementId>) -> Result<Agreement> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}");
         self.client.get(&url).send().json().await
     }
@@ -234,8 +282,9 @@ impl MarketProviderApi {
     /// Query termination Reason for specific Agreement.
     pub async fn get_agreement_termination_reason(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
     ) -> Result<AgreementTerminationReason> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/terminate/reason");
         self.client.get(&url).send().json().await
     }
@@ -284,6 +333,12 @@ impl MarketProviderApi {
             #[query] max_events,
             #[query] app_session_id,
         );
-        self.client.get(&url).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .events()
+            .await
+            .or_else(default_on_timeout)
     }
 }
@@ -5,11 +5,16 @@ use ya_client_model::market::{
     RequestorEvent,
 };
 
-use crate::{web::default_on_timeout, web::WebClient, web::WebInterface, Result};
+use crate::{
+    paging::single_page, web::default_on_timeout, web::timeout_secs, web::WebClient,
+    web::WebInterface, Result,
+};
 use chrono::{DateTime, TimeZone, Utc};
+use futures::Stream;
 use std::fmt::Display;
 use ya_client_model::market::scan::NewScan;
 use ya_client_model::NodeId;
+use ya_client_model::{AgreementId, SubscriptionId};
 
 /// Bindings for Requestor part of the Market API.
 #[derive(Clone)]
@@ -35,7 +40,7 @@ impl MarketRequestorApi {
     ///
     /// **Note**: it is an "atomic" operation, ie. as soon as Subscription is placed,
     /// the Demand is published on the market.
-    pub async fn subscribe(&self, demand: &NewDemand) -> Result<String> {
+    pub async fn subscribe(&self, demand: &NewDemand) -> Result<SubscriptionId> {
         self.client.post("demands").send_json(&demand).json().await
     }
 
@@ -45,7 +50,8 @@ impl MarketRequestorApi {
     }
 
     /// Stop subscription by invalidating a previously published Demand.
-    pub async fn unsubscribe(&self, subscription_id: &str) -> Result<()> {
+    pub async fn unsubscribe(&self, subscription_id: impl Into<SubscriptionId>) -> Result<()> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("demands/{subscription_id}");
         self.client.delete(&url).send().json().await
     }
@@ -60,12 +66,12 @@ impl MarketRequestorApi {
     /// Returns Proposal related events:
     ///
     /// * `ProposalEvent` - Indicates that there is new Offer Proposal for
-    /// this Demand.
+    ///   this Demand.
     ///
     /// * `ProposalRejectedEvent` - Indicates that the Provider has rejected
-    /// our previous Proposal related to this Demand. This effectively ends a
-    /// Negotiation chain - it explicitly indicates that the sender will not
-    /// create another counter-Proposal.
+    ///   our previous Proposal related to this Demand. This effectively ends a
+    ///   Negotiation chain - it explicitly indicates that the sender will not
+    ///   create another counter-Proposal.
     ///
     /// * `PropertyQueryEvent` - not supported yet.
     ///
@@ -79,25 +85,33 @@ impl MarketRequestorApi {
     #[rustfmt::skip]
     pub async fn collect(
         &self,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         timeout: Option<f32>,
         max_events: Option<i32>,
     ) -> Result<Vec<RequestorEvent>> {
+        let subscription_id = subscription_id.into();
         let url = url_format!(
             "demands/{subscription_id}/events",
             #[query] timeout,
             #[query] max_events,
         );
-        self.client.get(&url).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .events()
+            .await
+            .or_else(default_on_timeout)
     }
 
     /// Responds with a bespoke Demand to received Offer.
     pub async fn counter_proposal(
         &self,
         demand_proposal: &NewProposal,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         proposal_id: &str,
     ) -> Result<String> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("demands/{subscription_id}/proposals/{proposal_id}",);
         self.client
             .post(&url)
@@ -107,7 +121,12 @@ impl MarketRequestorApi {
     }
 
     /// Fetches Proposal (Offer) with given id.
-    pub async fn get_proposal(&self, subscription_id: &str, proposal_id: &str) -> Result<Proposal> {
+    pub async fn get_proposal(
+        &self,
+        subscription_id: impl Into<SubscriptionId>,
+        proposal_id: &str,
+    ) -> Result<Proposal> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("demands/{subscription_id}/proposals/{proposal_id}",);
         self.client.get(&url).send().json().await
     }
@@ -118,10 +137,11 @@ impl MarketRequestorApi {
     /// the sender will not create another counter-Proposal.
     pub async fn reject_proposal(
         &self,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         proposal_id: &str,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("demands/{subscription_id}/proposals/{proposal_id}/reject",);
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -141,7 +161,7 @@ impl MarketRequestorApi {
     /// for responses from the Provider.
     ///
     /// **Note**: Moves given Proposal to `Approved` state.
-    pub async fn create_agreement(&self, agreement: &AgreementProposal) -> Result<String> {
+    pub async fn create_agreement(&self, agreement: &AgreementProposal) -> Result<AgreementId> {
         self.client
             .post("agreements")
             .send_json(&agreement)
@@ -176,8 +196,27 @@ impl MarketRequestorApi {
         self.client.get(&url).send().json().await
     }
 
+    /// Streams agreements matching given filters.
+    ///
+    /// The endpoint does not limit the number of returned entries,
+    /// so all of them are fetched with a single request.
+    pub fn list_agreements_stream(
+        &self,
+        state: Option<State>,
+        before_date: Option<DateTime<Utc>>,
+        after_date: Option<DateTime<Utc>>,
+        app_session_id: Option<String>,
+    ) -> impl Stream<Item = Result<AgreementListEntry>> {
+        let api = self.clone();
+        single_page(async move {
+            api.list_agreements(state, before_date, after_date, app_session_id)
+                .await
+        })
+    }
+
     /// Fetches agreement with given agreement id.
-    pub async fn get_agreement(&self, agreement_id: &str) -> Result<Agreement> {
+    pub async fn get_agreement(&self, agreement_id: impl Into<AgreementId>) -> Result<Agreement> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}");
         self.client.get(&url).send().json().await
     }
@@ -187,9 +226,10 @@ impl MarketRequestorApi {
     #[rustfmt::skip]
     pub async fn confirm_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         app_session_id: Option<String>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!(
             "agreements/{agreement_id}/confirm",
             #[query] app_session_id,
@@ -206,9 +246,9 @@ impl MarketRequestorApi {
     /// It returns one of the following options:
     ///
     /// * `Ok` Agreement approved by the Provider.
-    ///  The Providers’s corresponding `approveAgreement` call returns `204`
-    ///  (Approved) **before** this endpoint on the Requestor side.
-    ///  The Provider is now ready to accept a request to start an Activity.
+    ///   The Providers’s corresponding `approveAgreement` call returns `204`
+    ///   (Approved) **before** this endpoint on the Requestor side.
+    ///   The Provider is now ready to accept a request to start an Activity.
     ///
     /// * `Err` - Indicates that Agreement is not approved.
     ///   - `408` Agreement not approved within given timeout. Try again.
@@ -217,26 +257,32 @@ impl MarketRequestorApi {
     ///
     /// Attached `ErrorMessage` contains further details:
     /// - `Rejected` - Indicates that the Provider has called
-    /// `rejectAgreement`, which effectively stops the Agreement handshake.
-    /// The Requestor may attempt to return to the Negotiation phase by
-    /// sending a new Proposal or to the Agreement phase by creating
-    /// new Agreement.
+    ///   `rejectAgreement`, which effectively stops the Agreement handshake.
+    ///   The Requestor may attempt to return to the Negotiation phase by
+    ///   sending a new Proposal or to the Agreement phase by creating
+    ///   new Agreement.
     /// - `Cancelled` - Indicates that the Requestor himself has called
-    /// `cancelAgreement`, which effectively stops the Agreement handshake.
+    ///   `cancelAgreement`, which effectively stops the Agreement handshake.
     /// - `Expired` - Indicates that Agreement validity period elapsed and it
-    /// was not approved, rejected nor cancelled.
+    ///   was not approved, rejected nor cancelled.
     /// - `Terminated` - Indicates that Agreement is already terminated.
     #[rustfmt::skip]
     pub async fn wait_for_approval(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         timeout: Option<f32>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!(
             "agreements/{agreement_id}/wait",
             #[query] timeout,
         );
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .json()
+            .await
     }
 
     /// Cancels Agreement.
@@ -248,9 +294,10 @@ impl MarketRequestorApi {
     /// Also the Provider's corresponding `approve_agreement` returns `Cancelled`.
     pub async fn cancel_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/cancel");
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -258,9 +305,10 @@ impl MarketRequestorApi {
     /// Terminates approved Agreement.
     pub async fn terminate_agreement(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
         reason: &Option<Reason>,
     ) -> Result<()> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/terminate");
         self.client.post(&url).send_json(&reason).json().await
     }
@@ -268,8 +316,9 @@ impl MarketRequestorApi {
     /// Query termination Reason for specific Agreement.
     pub async fn get_agreement_termination_reason(
         &self,
-        agreement_id: &str,
+        agreement_id: impl Into<AgreementId>,
     ) -> Result<AgreementTerminationReason> {
+        let agreement_id = agreement_id.into();
         let url = url_format!("agreements/{agreement_id}/terminate/reason");
         self.client.get(&url).send().json().await
     }
@@ -318,20 +367,27 @@ impl MarketRequestorApi {
             #[query] max_events,
             #[query] app_session_id,
         );
-        self.client.get(&url).send().json().await.or_else(default_on_timeout)
+        self.client
+            .get(&url)
+            .long_poll(timeout_secs(timeout))
+            .send()
+            .events()
+            .await
+            .or_else(default_on_timeout)
     }
 
-    pub async fn begin_scan(&self, scan_req: &NewScan) -> Result<String> {
+    pub async fn begin_scan(&self, scan_req: &NewScan) -> Result<SubscriptionId> {
         self.client.post("scan").send_json(&scan_req).json().await
     }
 
     pub async fn collect_scan(
         &self,
-        subscription_id: &str,
+        subscription_id: impl Into<SubscriptionId>,
         timeout: Option<f32>,
         max_events: Option<usize>,
         peer_id: Option<&NodeId>,
     ) -> Result<Vec<Offer>> {
+        let subscription_id = subscription_id.into();
         let url = url_format!(
             "scan/{subscription_id}/events",
             #[query]
@@ -343,13 +399,15 @@ impl MarketRequestorApi {
         );
         self.client
             .get(&url)
+            .long_poll(timeout_secs(timeout))
             .send()
             .json()
             .await
             .or_else(default_on_timeout)
     }
 
-    pub async fn end_scan(&self, subscription_id: &str) -> Result<()> {
+    pub async fn end_scan(&self, subscription_id: impl Into<SubscriptionId>) -> Result<()> {
+        let subscription_id = subscription_id.into();
         let url = url_format!("scan/{subscription_id}/events");
         self.client.delete(&url).send().json().await
     }
@@ -1,11 +1,22 @@
 use crate::error::Error;
 use crate::model::net::*;
-use crate::web::{WebClient, WebInterface};
-use actix_codec::Framed;
-use awc::http::Method;
-use awc::ws::Codec;
-use awc::BoxedSocket;
+use crate::web::{WebClient, WebInterface, WebSocket};
+use awc::http::{Method, StatusCode};
+use std::cell::Cell;
+use std::net::IpAddr;
 use std::ops::Not;
+use std::rc::Rc;
+use url::Url;
+
+mod forward;
+mod status;
+mod tcp;
+mod vpn;
+
+pub use forward::PortForward;
+pub use status::{Health, StatusEvent, StatusWatcher};
+pub use tcp::VpnTcpStream;
+pub use vpn::VpnNetwork;
 
 pub const NET_URL_ENV_VAR: &str = "YAGNA_NET_URL";
 
@@ -37,10 +48,24 @@ impl NetApi {
 #[deprecated(since = "0.7.0", note = "Please use `NetVpnApi` instead")]
 pub type NetRequestorApi = NetVpnApi;
 
+/// Layouts of the Net VPN API served by different versions of the daemon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetApiVersion {
+    /// Served under [`NET_API_V1_VPN_PATH`] by older daemons.
+    V1,
+    /// Served under [`NET_API_V2_VPN_PATH`].
+    V2,
+}
+
 /// Bindings for Requestor part of the Net VPN API.
+///
+/// The version of the API served by the daemon is probed with the first call
+/// and used by all clones of the interface.
 #[derive(Clone)]
 pub struct NetVpnApi {
     client: WebClient,
+    v1_client: Option<WebClient>,
+    version: Rc<Cell<Option<NetApiVersion>>>,
 }
 
 impl WebInterface for NetVpnApi {
@@ -48,78 +73,134 @@ impl WebInterface for NetVpnApi {
     const API_SUFFIX: &'static str = ya_client_model::net::NET_API_V2_VPN_PATH;
 
     fn from_client(client: WebClient) -> Self {
-        NetVpnApi { client }
+        let v1_client = v1_url(&client.base_url).map(|url| {
+            let mut v1_client = client.clone();
+            v1_client.base_url = url.into();
+            v1_client
+        });
+        NetVpnApi {
+            client,
+            v1_client,
+            version: Default::default(),
+        }
     }
 }
 
 impl NetVpnApi {
+    /// Uses the given version of the API instead of probing the daemon.
+    pub fn with_version(self, version: NetApiVersion) -> Self {
+        self.version.set(Some(version));
+        self
+    }
+
+    /// Version of the API served by the daemon.
+    ///
+    /// Falls back to v1 if the daemon does not serve v2 networks. Interfaces created
+    /// at a custom url always use v2.
+    pub async fn version(&self) -> Result<NetApiVersion> {
+        if let Some(version) = self.version.get() {
+            return Ok(version);
+        }
+        let version = match &self.v1_client {
+            None => NetApiVersion::V2,
+            Some(v1_client) => match self.client.get("net").send().json::<Vec<Network>>().await {
+                Ok(_) => NetApiVersion::V2,
+                Err(Error::HttpError { code, .. }) if code == StatusCode::NOT_FOUND => {
+                    v1_client.get("net").send().json::<Vec<Network>>().await?;
+                    NetApiVersion::V1
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        log::debug!("Using Net API {version:?}");
+        self.version.set(Some(version));
+        Ok(version)
+    }
+
+    async fn client(&self) -> Result<&WebClient> {
+        Ok(match (self.version().await?, &self.v1_client) {
+            (NetApiVersion::V1, Some(v1_client)) => v1_client,
+            _ => &self.client,
+        })
+    }
+
     /// Retrieves requestor's virtual private networks.
     pub async fn get_networks(&self) -> Result<Vec<Network>> {
-        self.client.get("net").send().json().await
+        self.client().await?.get("net").send().json().await
     }
 
     /// Registers a new virtual private network overlay on the network.
     pub async fn create_network(&self, network: &NewNetwork) -> Result<Network> {
-        self.client.post("net").send_json(&network).json().await
+        self.client()
+            .await?
+            .post("net")
+            .send_json(&network)
+            .json()
+            .await
     }
 
     /// Retrieves a requestor's virtual private network.
     pub async fn get_network(&self, network_id: &str) -> Result<Network> {
         let url = url_format!("net/{network_id}");
-        self.client.get(&url).send().json().await
+        self.client().await?.get(&url).send().json().await
     }
 
     /// Unregisters an existing virtual private network overlay on the network.
     pub async fn remove_network(&self, network_id: &str) -> Result<()> {
         let url = url_format!("net/{network_id}");
-        self.client.delete(&url).send().json().await
+        self.client().await?.delete(&url).send().json().await
     }
 
     /// Retrieves requestor's addresses in a virtual private network.
     pub async fn get_addresses(&self, network_id: &str) -> Result<Vec<Address>> {
         let url = url_format!("net/{network_id}/addresses");
-        self.client.get(&url).send().json().await
+        self.client().await?.get(&url).send().json().await
     }
 
     /// Assigns a new address of the requestor in an existing private network.
     pub async fn add_address(&self, network_id: &str, address: &Address) -> Result<()> {
         let url = url_format!("net/{network_id}/addresses");
-        self.client.post(&url).send_json(&address).json().await
+        self.client()
+            .await?
+            .post(&url)
+            .send_json(&address)
+            .json()
+            .await
     }
 
     /// Retrieves nodes within a virtual private network.
     pub async fn get_nodes(&self, network_id: &str) -> Result<Vec<Node>> {
         let url = url_format!("net/{network_id}/nodes");
-        self.client.get(&url).send().json().await
+        self.client().await?.get(&url).send().json().await
     }
 
     /// Registers a node in a virtual private network.
     pub async fn add_node(&self, network_id: &str, node: &Node) -> Result<()> {
         let url = url_format!("net/{network_id}/nodes");
-        self.client.post(&url).send_json(&node).json().await
+        self.client()
+            .await?
+            .post(&url)
+            .send_json(&node)
+            .json()
+            .await
     }
 
     /// Unregisters an existing node in a virtual private network.
     pub async fn remove_node(&self, network_id: &str, node_id: &str) -> Result<()> {
         let url = url_format!("net/{network_id}/nodes/{node_id}");
-        self.client.post(&url).send().json().await
+        self.client().await?.post(&url).send().json().await
     }
 
     /// Lists TCP connections
     pub async fn list_tcp(&self, network_id: &str) -> Result<Vec<Connection>> {
         let url = url_format!("net/{network_id}/tcp");
-        self.client.get(&url).send().json().await
+        self.client().await?.get(&url).send().json().await
     }
 
     /// Creates a new TCP connection
-    pub async fn connect_tcp(
-        &self,
-        network_id: &str,
-        ip: &str,
-        port: u16,
-    ) -> Result<Framed<BoxedSocket, Codec>> {
+    pub async fn connect_tcp(&self, network_id: &str, ip: IpAddr, port: u16) -> Result<WebSocket> {
         let url = url_format!("net/{network_id}/tcp/{ip}/{port}");
-        let (mut res, conn) = self.client.ws(&url).await?;
+        let (mut res, conn) = self.client().await?.ws(&url).await?;
 
         let status = res.status();
         if status.is_success().not() && status.is_informational().not() {
@@ -134,4 +215,82 @@ impl NetVpnApi {
 
         Ok(conn)
     }
+
+    /// Creates a new TCP connection, readable and writable as a byte stream.
+    pub async fn connect_tcp_stream(
+        &self,
+        network_id: &str,
+        ip: IpAddr,
+        port: u16,
+    ) -> Result<VpnTcpStream> {
+        let conn = self.connect_tcp(network_id, ip, port).await?;
+        Ok(VpnTcpStream::new(conn))
+    }
+}
+
+/// Url of the v1 API next to the default v2 one.
+fn v1_url(v2_url: &Url) -> Option<Url> {
+    let path = v2_url.path().trim_end_matches('/');
+    let root = path.strip_suffix(NET_API_V2_VPN_PATH)?;
+    let mut url = v2_url.clone();
+    url.set_path(&format!("{root}{NET_API_V1_VPN_PATH}/"));
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Request, Response};
+    use std::thread::JoinHandle;
+
+    /// Serves `count` requests as a daemon with the v1 API only.
+    fn serve_v1(count: usize) -> (String, JoinHandle<Vec<Request>>) {
+        testing::serve(count, |request| {
+            match request.line.contains(NET_API_V1_VPN_PATH) {
+                true => Response::json("[]"),
+                false => Response::json("[]").with_status("404 Not Found"),
+            }
+        })
+    }
+
+    #[test]
+    fn v1_url_next_to_v2() {
+        let v2: Url = "http://127.0.0.1:7465/net-api/v2/vpn/".parse().unwrap();
+        assert_eq!(
+            v1_url(&v2).unwrap().as_str(),
+            "http://127.0.0.1:7465/net-api/v1/"
+        );
+        assert_eq!(v1_url(&"http://127.0.0.1/vpn/".parse().unwrap()), None);
+    }
+
+    #[actix_rt::test]
+    async fn falls_back_to_v1() {
+        let (url, server) = serve_v1(3);
+        let client = WebClient::builder().api_url(url.parse().unwrap()).build();
+        let api: NetVpnApi = client.interface().unwrap();
+
+        assert_eq!(api.version().await.unwrap(), NetApiVersion::V1);
+        assert!(api.clone().get_networks().await.unwrap().is_empty());
+        let requests = server.join().unwrap();
+        assert_eq!(
+            requests.iter().map(|r| r.line.as_str()).collect::<Vec<_>>(),
+            [
+                "GET /net-api/v2/vpn/net HTTP/1.1",
+                "GET /net-api/v1/net HTTP/1.1",
+                "GET /net-api/v1/net HTTP/1.1",
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn pinned_version() {
+        let client = WebClient::builder()
+            .api_url("http://127.0.0.1:1/".parse().unwrap())
+            .build();
+        let api = client
+            .interface::<NetVpnApi>()
+            .unwrap()
+            .with_version(NetApiVersion::V1);
+        assert_eq!(api.version().await.unwrap(), NetApiVersion::V1);
+    }
 }
@@ -0,0 +1,170 @@
+//! Forwarding of local TCP ports into a VPN
+use futures::future::{select, Either};
+use std::cell::Cell;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::rc::Rc;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio_util::sync::CancellationToken;
+
+use ya_client_model::net::Connection;
+
+use super::{NetVpnApi, Result};
+
+/// Forwards connections accepted on a local TCP port to an address within a VPN.
+///
+/// Every accepted connection is piped through its own
+/// [`NetVpnApi::connect_tcp_stream`](super::NetVpnApi::connect_tcp_stream) channel.
+/// Forwarding runs on the current thread and stops, closing all forwarded
+/// connections, when the value is dropped.
+pub struct PortForward {
+    api: NetVpnApi,
+    network_id: String,
+    remote: SocketAddr,
+    local_addr: SocketAddr,
+    active: Rc<Cell<usize>>,
+    cancel: CancellationToken,
+}
+
+impl PortForward {
+    /// Binds `local` and starts forwarding to the `remote` address within the network.
+    pub async fn bind(
+        api: NetVpnApi,
+        network_id: &str,
+        remote: SocketAddr,
+        local: impl ToSocketAddrs,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(local).await?;
+        let forward = PortForward {
+            api,
+            network_id: network_id.to_string(),
+            remote,
+            local_addr: listener.local_addr()?,
+            active: Default::default(),
+            cancel: CancellationToken::new(),
+        };
+        tokio::task::spawn_local(forward.clone_channel().accept(listener));
+        Ok(forward)
+    }
+
+    /// Local address accepting connections.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Address within the network the connections are forwarded to.
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote
+    }
+
+    /// Number of connections being forwarded at the moment.
+    pub fn active_connections(&self) -> usize {
+        self.active.get()
+    }
+
+    /// TCP connections to the remote address, as seen by the daemon.
+    pub async fn connections(&self) -> Result<Vec<Connection>> {
+        let connections = self.api.list_tcp(&self.network_id).await?;
+        Ok(connections
+            .into_iter()
+            .filter(|c| c.remote_ip == self.remote.ip() && c.remote_port == self.remote.port())
+            .collect())
+    }
+
+    fn clone_channel(&self) -> Channel {
+        Channel {
+            api: self.api.clone(),
+            network_id: self.network_id.clone(),
+            remote: self.remote,
+            active: self.active.clone(),
+            cancel: self.cancel.clone(),
+        }
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[derive(Clone)]
+struct Channel {
+    api: NetVpnApi,
+    network_id: String,
+    remote: SocketAddr,
+    active: Rc<Cell<usize>>,
+    cancel: CancellationToken,
+}
+
+impl Channel {
+    async fn accept(self, listener: TcpListener) {
+        loop {
+            let cancelled = pin!(self.cancel.cancelled());
+            let accept = pin!(listener.accept());
+            match select(cancelled, accept).await {
+                Either::Left(_) => break,
+                Either::Right((Ok((socket, peer)), _)) => {
+                    log::debug!("Forwarding connection from {peer} to {}", self.remote);
+                    tokio::task::spawn_local(self.clone().forward(socket));
+                }
+                Either::Right((Err(e), _)) => log::warn!("Failed to accept connection: {e}"),
+            }
+        }
+    }
+
+    async fn forward(self, mut socket: TcpStream) {
+        self.active.set(self.active.get() + 1);
+        let (ip, port) = (self.remote.ip(), self.remote.port());
+
+        match self
+            .api
+            .connect_tcp_stream(&self.network_id, ip, port)
+            .await
+        {
+            Ok(mut stream) => {
+                let cancelled = pin!(self.cancel.cancelled());
+                let copy = pin!(tokio::io::copy_bidirectional(&mut socket, &mut stream));
+                if let Either::Right((Err(e), _)) = select(cancelled, copy).await {
+                    log::debug!("Connection to {} closed: {e}", self.remote);
+                }
+            }
+            Err(e) => log::warn!("Failed to connect to {}: {e}", self.remote),
+        }
+        self.active.set(self.active.get() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::web::{WebClient, WebInterface};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[actix_rt::test]
+    async fn forwards_connections() {
+        let server = testing::ws_echo_server().await;
+        let client = WebClient::builder()
+            .api_url(format!("http://{server}/").parse().unwrap())
+            .build();
+        let remote = "192.168.0.2:8080".parse().unwrap();
+        let forward =
+            PortForward::bind(NetVpnApi::from_client(client), "vpn", remote, "127.0.0.1:0")
+                .await
+                .unwrap();
+        assert_eq!(forward.remote_addr(), remote);
+
+        let mut socket = TcpStream::connect(forward.local_addr()).await.unwrap();
+        socket.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        socket.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+        assert_eq!(forward.active_connections(), 1);
+
+        drop(forward);
+        let read = tokio::time::timeout(Duration::from_secs(5), socket.read(&mut buf)).await;
+        assert!(matches!(read, Ok(Ok(0))));
+    }
+}
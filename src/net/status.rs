@@ -0,0 +1,202 @@
+//! Watching the connection status of the daemon
+use futures::{stream, Stream};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use ya_client_model::net::Status;
+
+use super::NetApi;
+
+/// Connectivity of the daemon to the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Health {
+    /// Status has not been retrieved yet.
+    Unknown,
+    /// Sessions with other nodes are established.
+    Healthy,
+    /// The daemon has no sessions with other nodes.
+    NoSessions,
+    /// Status could not be retrieved.
+    Unreachable,
+}
+
+/// Change of the status observed by a [`StatusWatcher`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusEvent {
+    HealthChanged {
+        previous: Health,
+        current: Health,
+    },
+    PublicIpChanged {
+        previous: Option<String>,
+        current: Option<String>,
+    },
+    /// Number of sessions dropped to zero.
+    SessionsDropped {
+        previous: usize,
+    },
+}
+
+/// Polls the status of the daemon, reporting its changes.
+///
+/// Created with [`NetApi::watch_status`].
+pub struct StatusWatcher {
+    api: NetApi,
+    interval: Duration,
+    status: Option<Status>,
+    health: Health,
+    events: VecDeque<StatusEvent>,
+    polled: bool,
+}
+
+impl NetApi {
+    /// Watches the connection status, polling it every `interval`.
+    pub fn watch_status(&self, interval: Duration) -> StatusWatcher {
+        StatusWatcher {
+            api: self.clone(),
+            interval,
+            status: None,
+            health: Health::Unknown,
+            events: VecDeque::new(),
+            polled: false,
+        }
+    }
+}
+
+impl StatusWatcher {
+    /// Health as of the last poll.
+    pub fn health(&self) -> Health {
+        self.health
+    }
+
+    /// Last successfully retrieved status.
+    pub fn status(&self) -> Option<&Status> {
+        self.status.as_ref()
+    }
+
+    /// Polls the status until it changes.
+    pub async fn next_event(&mut self) -> StatusEvent {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return event;
+            }
+            if self.polled {
+                tokio::time::sleep(self.interval).await;
+            }
+            self.poll().await;
+        }
+    }
+
+    /// Streams the changes of the status.
+    pub fn into_stream(self) -> impl Stream<Item = StatusEvent> {
+        stream::unfold(self, |mut watcher| async move {
+            let event = watcher.next_event().await;
+            Some((event, watcher))
+        })
+    }
+
+    async fn poll(&mut self) {
+        self.polled = true;
+        let health = match self.api.get_status().await {
+            Ok(status) => {
+                let health = match status.sessions {
+                    0 => Health::NoSessions,
+                    _ => Health::Healthy,
+                };
+                self.update(status);
+                health
+            }
+            Err(e) => {
+                log::debug!("Failed to retrieve net status: {e}");
+                Health::Unreachable
+            }
+        };
+        if health != self.health {
+            self.events.push_back(StatusEvent::HealthChanged {
+                previous: self.health,
+                current: health,
+            });
+            self.health = health;
+        }
+    }
+
+    fn update(&mut self, status: Status) {
+        if let Some(previous) = self.status.take() {
+            if previous.public_ip != status.public_ip {
+                self.events.push_back(StatusEvent::PublicIpChanged {
+                    previous: previous.public_ip,
+                    current: status.public_ip.clone(),
+                });
+            }
+            if previous.sessions > 0 && status.sessions == 0 {
+                self.events.push_back(StatusEvent::SessionsDropped {
+                    previous: previous.sessions,
+                });
+            }
+        }
+        self.status = Some(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Response};
+    use crate::web::{WebClient, WebInterface};
+
+    /// Serves the statuses in order, then stops accepting connections.
+    fn serve(statuses: &'static [&'static str]) -> String {
+        let mut statuses = statuses.iter();
+        let (url, _) = testing::serve(statuses.len(), move |_| {
+            Response::json(*statuses.next().unwrap())
+        });
+        url
+    }
+
+    #[actix_rt::test]
+    async fn reports_changes() {
+        let url = serve(&[
+            r#"{"nodeId":"0x0000000000000000000000000000000000000001","publicIp":"1.1.1.1","sessions":2}"#,
+            r#"{"nodeId":"0x0000000000000000000000000000000000000001","publicIp":"1.1.1.1","sessions":3}"#,
+            r#"{"nodeId":"0x0000000000000000000000000000000000000001","publicIp":"2.2.2.2","sessions":0}"#,
+        ]);
+        let client = WebClient::builder().api_url(url.parse().unwrap()).build();
+        let mut watcher = NetApi::from_client(client).watch_status(Duration::from_millis(1));
+        assert_eq!(watcher.health(), Health::Unknown);
+
+        assert_eq!(
+            watcher.next_event().await,
+            StatusEvent::HealthChanged {
+                previous: Health::Unknown,
+                current: Health::Healthy
+            }
+        );
+        assert_eq!(watcher.health(), Health::Healthy);
+        assert_eq!(
+            watcher.next_event().await,
+            StatusEvent::PublicIpChanged {
+                previous: Some("1.1.1.1".into()),
+                current: Some("2.2.2.2".into())
+            }
+        );
+        assert_eq!(
+            watcher.next_event().await,
+            StatusEvent::SessionsDropped { previous: 3 }
+        );
+        assert_eq!(
+            watcher.next_event().await,
+            StatusEvent::HealthChanged {
+                previous: Health::Healthy,
+                current: Health::NoSessions
+            }
+        );
+        assert_eq!(watcher.status().unwrap().sessions, 0);
+        assert_eq!(
+            watcher.next_event().await,
+            StatusEvent::HealthChanged {
+                previous: Health::NoSessions,
+                current: Health::Unreachable
+            }
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Byte stream over a VPN TCP connection
+use actix_codec::Framed;
+use actix_http::ws::Item;
+use awc::error::WsProtocolError;
+use awc::ws::{CloseCode, CloseReason, Codec, Frame, Message};
+use awc::BoxedSocket;
+use bytes::Bytes;
+use futures::{Sink, Stream};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::web::WebSocket;
+
+/// Largest payload of a single frame written to the connection.
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// TCP connection within a VPN, as returned by
+/// [`NetVpnApi::connect_tcp_stream`](super::NetVpnApi::connect_tcp_stream).
+///
+/// Reads the payload of binary frames and writes data as binary frames, answering pings
+/// on the way. Writes are subject to backpressure of the underlying WebSocket.
+/// Shutting down the stream sends a close frame; reading ends once the remote side
+/// closes the connection, with an error unless it has been closed normally.
+pub struct VpnTcpStream {
+    framed: WebSocket,
+    read_buf: Bytes,
+    pending: Option<Message>,
+    read_closed: bool,
+    write_closed: bool,
+}
+
+impl VpnTcpStream {
+    pub fn new(framed: impl Into<WebSocket>) -> Self {
+        VpnTcpStream {
+            framed: framed.into(),
+            read_buf: Bytes::new(),
+            pending: None,
+            read_closed: false,
+            write_closed: false,
+        }
+    }
+
+    pub fn into_inner(self) -> Framed<BoxedSocket, Codec> {
+        self.framed.into_inner()
+    }
+
+    /// Sends the pending control frame, if any.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.pending.is_some() {
+            ready!(Pin::new(&mut self.framed).poll_ready(cx)).map_err(to_io_error)?;
+            let message = self.pending.take().unwrap();
+            Pin::new(&mut self.framed)
+                .start_send(message)
+                .map_err(to_io_error)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn on_close(&mut self, reason: Option<CloseReason>) -> io::Result<()> {
+        self.read_closed = true;
+        if !self.write_closed {
+            self.write_closed = true;
+            self.pending = Some(Message::Close(reason.clone()));
+        }
+        match reason {
+            None => Ok(()),
+            Some(CloseReason {
+                code: CloseCode::Normal,
+                ..
+            }) => Ok(()),
+            Some(reason) => Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                format!(
+                    "connection closed: {:?} {}",
+                    reason.code,
+                    reason.description.unwrap_or_default()
+                ),
+            )),
+        }
+    }
+}
+
+impl From<Framed<BoxedSocket, Codec>> for VpnTcpStream {
+    fn from(framed: Framed<BoxedSocket, Codec>) -> Self {
+        VpnTcpStream::new(framed)
+    }
+}
+
+impl AsyncRead for VpnTcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.read_buf.is_empty() {
+                let n = this.read_buf.len().min(buf.remaining());
+                buf.put_slice(&this.read_buf.split_to(n));
+                return Poll::Ready(Ok(()));
+            }
+            if this.read_closed {
+                // Reply to the close frame before reporting the end of stream
+                if ready!(this.poll_pending(cx)).is_ok() {
+                    let _ = ready!(Pin::new(&mut this.framed).poll_flush(cx));
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            // Answering a ping must not block reading
+            if let Poll::Ready(Err(e)) = this.poll_pending(cx) {
+                return Poll::Ready(Err(e));
+            }
+            let _ = Pin::new(&mut this.framed).poll_flush(cx);
+
+            let frame = match ready!(Pin::new(&mut this.framed).poll_next(cx)) {
+                Some(frame) => frame.map_err(to_io_error)?,
+                None => {
+                    this.read_closed = true;
+                    continue;
+                }
+            };
+            match frame {
+                Frame::Binary(data)
+                | Frame::Continuation(Item::FirstBinary(data))
+                | Frame::Continuation(Item::Continue(data))
+                | Frame::Continuation(Item::Last(data)) => this.read_buf = data,
+                Frame::Ping(data) => {
+                    if !this.write_closed {
+                        this.pending = Some(Message::Pong(data));
+                    }
+                }
+                Frame::Pong(_) => {}
+                Frame::Close(reason) => this.on_close(reason)?,
+                Frame::Text(_) | Frame::Continuation(Item::FirstText(_)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected text frame",
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for VpnTcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.write_closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        ready!(this.poll_pending(cx))?;
+        ready!(Pin::new(&mut this.framed).poll_ready(cx)).map_err(to_io_error)?;
+
+        let n = buf.len().min(MAX_FRAME_SIZE);
+        let data = Bytes::copy_from_slice(&buf[..n]);
+        Pin::new(&mut this.framed)
+            .start_send(Message::Binary(data))
+            .map_err(to_io_error)?;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.framed)
+            .poll_flush(cx)
+            .map_err(to_io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.write_closed {
+            this.write_closed = true;
+            this.pending = Some(Message::Close(Some(CloseCode::Normal.into())));
+        }
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.framed)
+            .poll_flush(cx)
+            .map_err(to_io_error)
+    }
+}
+
+fn to_io_error(e: WsProtocolError) -> io::Error {
+    match e {
+        WsProtocolError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+    fn pair() -> (VpnTcpStream, Framed<DuplexStream, Codec>) {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = Framed::new(Box::new(client) as BoxedSocket, Codec::new().client_mode());
+        (client.into(), Framed::new(server, Codec::new()))
+    }
+
+    #[actix_rt::test]
+    async fn reads_binary_frames_and_answers_pings() {
+        let (mut stream, mut server) = pair();
+        server.send(Message::Binary("hello ".into())).await.unwrap();
+        server.send(Message::Ping("p".into())).await.unwrap();
+        server.send(Message::Binary("world".into())).await.unwrap();
+        server
+            .send(Message::Close(Some(CloseCode::Normal.into())))
+            .await
+            .unwrap();
+
+        let mut data = String::new();
+        stream.read_to_string(&mut data).await.unwrap();
+        assert_eq!(data, "hello world");
+        assert!(matches!(server.next().await, Some(Ok(Frame::Pong(p))) if p == "p"));
+        assert!(matches!(server.next().await, Some(Ok(Frame::Close(_)))));
+    }
+
+    #[actix_rt::test]
+    async fn writes_binary_frames() {
+        let (mut stream, mut server) = pair();
+        stream.write_all(b"request").await.unwrap();
+        stream.shutdown().await.unwrap();
+
+        assert!(matches!(server.next().await, Some(Ok(Frame::Binary(b))) if b == "request"));
+        assert!(matches!(
+            server.next().await,
+            Some(Ok(Frame::Close(Some(CloseReason {
+                code: CloseCode::Normal,
+                ..
+            }))))
+        ));
+        assert!(stream.write_all(b"more").await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn abnormal_close_is_an_error() {
+        let (mut stream, mut server) = pair();
+        server
+            .send(Message::Close(Some(CloseCode::Error.into())))
+            .await
+            .unwrap();
+
+        let err = stream.read_to_end(&mut Vec::new()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionAborted);
+    }
+}
@@ -0,0 +1,316 @@
+//! Requestor-side bookkeeping of a virtual private network
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use ya_client_model::activity::{self, ExeScriptCommand};
+use ya_client_model::net::{Address, IpNet, Network, NewNetwork, Node};
+use ya_client_model::NodeId;
+
+use super::{NetVpnApi, Result};
+use crate::Error;
+
+/// Virtual private network created by the requestor, with addresses of the joining
+/// provider nodes allocated from its subnet.
+///
+/// Addresses are allocated from the hosts of the subnet in order, skipping the gateway.
+/// The first one is assigned to the requestor.
+///
+/// The network is removed on drop, unless [`VpnNetwork::remove`] has been called.
+/// Removal on drop is spawned on the current thread, so the value has to be dropped
+/// within the runtime it has been created in.
+pub struct VpnNetwork {
+    api: NetVpnApi,
+    network: Network,
+    net: IpNet,
+    requestor_ip: IpAddr,
+    nodes: RefCell<HashMap<NodeId, IpAddr>>,
+    removed: bool,
+}
+
+impl VpnNetwork {
+    /// Creates a network spanning `net` and assigns the requestor an address in it.
+    pub async fn create(api: NetVpnApi, net: IpNet) -> Result<Self> {
+        let net = net.trunc();
+        let network = api.create_network(&NewNetwork::new(net)).await?;
+        let requestor_ip =
+            free_ip(&net, network.gateway, []).ok_or_else(|| no_free_address(&network.id))?;
+
+        let vpn = VpnNetwork {
+            api,
+            network,
+            net,
+            requestor_ip,
+            nodes: Default::default(),
+            removed: false,
+        };
+        let address = Address { ip: requestor_ip };
+        vpn.api.add_address(vpn.id(), &address).await?;
+        Ok(vpn)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.network.id
+    }
+
+    pub fn net(&self) -> IpNet {
+        self.net
+    }
+
+    pub fn gateway(&self) -> IpAddr {
+        self.network.gateway
+    }
+
+    pub fn requestor_ip(&self) -> IpAddr {
+        self.requestor_ip
+    }
+
+    /// Address allocated to the node, if it has joined the network.
+    pub fn node_ip(&self, node_id: &NodeId) -> Option<IpAddr> {
+        self.nodes.borrow().get(node_id).copied()
+    }
+
+    /// Allocates a free address to the node and registers it in the network.
+    ///
+    /// Returns the address already allocated to the node, if any.
+    /// The address is released if the registration fails.
+    pub async fn add_node(&self, node_id: NodeId) -> Result<IpAddr> {
+        if let Some(ip) = self.node_ip(&node_id) {
+            return Ok(ip);
+        }
+        // reserve the address before registering the node,
+        // so that concurrent calls do not pick the same one
+        let ip = {
+            let mut nodes = self.nodes.borrow_mut();
+            let taken = nodes.values().copied().chain([self.requestor_ip]);
+            let ip = free_ip(&self.net, self.network.gateway, taken)
+                .ok_or_else(|| no_free_address(self.id()))?;
+            nodes.insert(node_id, ip);
+            ip
+        };
+        let node = Node {
+            id: node_id.to_string(),
+            ip,
+        };
+        if let Err(e) = self.api.add_node(self.id(), &node).await {
+            self.nodes.borrow_mut().remove(&node_id);
+            return Err(e);
+        }
+        Ok(ip)
+    }
+
+    /// Unregisters the node from the network, releasing its address.
+    pub async fn remove_node(&self, node_id: &NodeId) -> Result<()> {
+        if self.node_ip(node_id).is_none() {
+            return Ok(());
+        }
+        self.api
+            .remove_node(self.id(), &node_id.to_string())
+            .await?;
+        self.nodes.borrow_mut().remove(node_id);
+        Ok(())
+    }
+
+    /// `Deploy` command joining an activity of the node to the network.
+    ///
+    /// Nodes are resolvable by their ids, as hostnames. Returns `None` if the node
+    /// has not been added to the network.
+    pub fn deploy(&self, node_id: &NodeId) -> Option<ExeScriptCommand> {
+        let node_ip = self.node_ip(node_id)?;
+        let nodes = self.nodes.borrow();
+        let net = activity::Network {
+            id: self.network.id.clone(),
+            ip: self.net.network(),
+            mask: Some(self.net.netmask()),
+            gateway: Some(self.network.gateway),
+            node_ip,
+            nodes: nodes
+                .iter()
+                .map(|(node_id, ip)| (*ip, node_id.to_string()))
+                .collect(),
+        };
+        let hosts = nodes
+            .iter()
+            .map(|(node_id, ip)| (node_id.to_string(), *ip))
+            .collect();
+
+        Some(ExeScriptCommand::Deploy {
+            net: vec![net],
+            hosts,
+            hostname: None,
+            volumes: None,
+            env: Default::default(),
+            progress: None,
+        })
+    }
+
+    /// Removes the network.
+    pub async fn remove(mut self) -> Result<()> {
+        self.removed = true;
+        self.api.remove_network(self.id()).await
+    }
+}
+
+impl Drop for VpnNetwork {
+    fn drop(&mut self) {
+        if self.removed {
+            return;
+        }
+        if tokio::runtime::Handle::try_current().is_err() {
+            log::warn!(
+                "VPN network {} dropped outside of a runtime, not removing it",
+                self.network.id
+            );
+            return;
+        }
+        let api = self.api.clone();
+        let id = self.network.id.clone();
+        tokio::task::spawn_local(async move {
+            if let Err(e) = api.remove_network(&id).await {
+                log::warn!("Failed to remove VPN network {id}: {e}");
+            }
+        });
+    }
+}
+
+fn free_ip(
+    net: &IpNet,
+    gateway: IpAddr,
+    taken: impl IntoIterator<Item = IpAddr>,
+) -> Option<IpAddr> {
+    let taken: Vec<_> = taken.into_iter().collect();
+    net.hosts().find(|ip| *ip != gateway && !taken.contains(ip))
+}
+
+fn no_free_address(network_id: &str) -> Error {
+    Error::NoFreeAddress {
+        network_id: network_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Request, Response};
+    use crate::web::{WebClient, WebInterface};
+    use std::thread::JoinHandle;
+
+    /// Serves `count` requests of a network manager.
+    fn serve(count: usize) -> (String, JoinHandle<Vec<Request>>) {
+        testing::serve(count, |request| {
+            match request.line.starts_with("POST /net ") {
+                true => Response::json(
+                    r#"{"id":"vpn","ip":"10.0.0.0","mask":"255.255.255.248","gateway":"10.0.0.1"}"#,
+                ),
+                false => Response::json(""),
+            }
+        })
+    }
+
+    fn lines(requests: &[Request]) -> Vec<&str> {
+        requests.iter().map(|r| r.line.as_str()).collect()
+    }
+
+    fn api(url: &str) -> NetVpnApi {
+        let client = WebClient::builder().api_url(url.parse().unwrap()).build();
+        NetVpnApi::from_client(client)
+    }
+
+    fn node(n: u8) -> NodeId {
+        NodeId::from([n; 20])
+    }
+
+    #[actix_rt::test]
+    async fn allocates_addresses() {
+        let (url, server) = serve(7);
+        let vpn = VpnNetwork::create(api(&url), "10.0.0.3/29".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(vpn.net(), "10.0.0.0/29".parse::<IpNet>().unwrap());
+        assert_eq!(vpn.requestor_ip(), "10.0.0.2".parse::<IpAddr>().unwrap());
+
+        let ip = vpn.add_node(node(1)).await.unwrap();
+        assert_eq!(ip, "10.0.0.3".parse::<IpAddr>().unwrap());
+        assert_eq!(vpn.add_node(node(1)).await.unwrap(), ip);
+        assert_eq!(
+            vpn.add_node(node(2)).await.unwrap(),
+            "10.0.0.4".parse::<IpAddr>().unwrap()
+        );
+
+        vpn.remove_node(&node(1)).await.unwrap();
+        assert_eq!(vpn.node_ip(&node(1)), None);
+        assert_eq!(vpn.add_node(node(3)).await.unwrap(), ip);
+
+        vpn.remove().await.unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[2].body.contains(r#""ip":"10.0.0.3""#));
+        assert_eq!(
+            lines(&requests),
+            [
+                "POST /net HTTP/1.1",
+                "POST /net/vpn/addresses HTTP/1.1",
+                "POST /net/vpn/nodes HTTP/1.1",
+                "POST /net/vpn/nodes HTTP/1.1",
+                &format!("POST /net/vpn/nodes/{} HTTP/1.1", node(1)),
+                "POST /net/vpn/nodes HTTP/1.1",
+                "DELETE /net/vpn HTTP/1.1",
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn deploy_command() {
+        let (url, server) = serve(4);
+        let vpn = VpnNetwork::create(api(&url), "10.0.0.0/29".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(vpn.deploy(&node(1)).is_none());
+
+        let ip = vpn.add_node(node(1)).await.unwrap();
+        let Some(ExeScriptCommand::Deploy { net, hosts, .. }) = vpn.deploy(&node(1)) else {
+            panic!("expected a Deploy command");
+        };
+        assert_eq!(net[0].id, "vpn");
+        assert_eq!(net[0].node_ip, ip);
+        assert_eq!(net[0].mask, Some("255.255.255.248".parse().unwrap()));
+        assert_eq!(net[0].nodes[&ip], node(1).to_string());
+        assert_eq!(hosts[&node(1).to_string()], ip);
+
+        vpn.remove().await.unwrap();
+        server.join().unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn concurrent_allocations() {
+        let (url, server) = testing::serve(7, |request| match request.line.as_str() {
+            "POST /net HTTP/1.1" => Response::json(
+                r#"{"id":"vpn","ip":"10.0.0.0","mask":"255.255.255.248","gateway":"10.0.0.1"}"#,
+            ),
+            _ if request.body.contains(&node(2).to_string()) => {
+                Response::status("500 Internal Server Error")
+            }
+            _ => Response::json(""),
+        });
+        let vpn = VpnNetwork::create(api(&url), "10.0.0.0/29".parse().unwrap())
+            .await
+            .unwrap();
+
+        let (a, b, c) = futures::join!(
+            vpn.add_node(node(1)),
+            vpn.add_node(node(2)),
+            vpn.add_node(node(3))
+        );
+        assert_eq!(a.unwrap(), "10.0.0.3".parse::<IpAddr>().unwrap());
+        assert!(b.is_err());
+        assert_eq!(c.unwrap(), "10.0.0.5".parse::<IpAddr>().unwrap());
+        assert_eq!(vpn.node_ip(&node(2)), None);
+
+        // the address of the failed registration is free again
+        assert_eq!(
+            vpn.add_node(node(4)).await.unwrap(),
+            "10.0.0.4".parse::<IpAddr>().unwrap()
+        );
+        vpn.remove().await.unwrap();
+        server.join().unwrap();
+    }
+}
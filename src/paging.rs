@@ -0,0 +1,263 @@
+//! Pagination over list endpoints filtered by `afterTimestamp`
+use chrono::{DateTime, Utc};
+use futures::future::{ready, Future};
+use futures::{stream, Stream, StreamExt};
+use std::collections::HashSet;
+
+use crate::model::payment::payment::Signed;
+use crate::model::payment::{Allocation, DebitNote, Invoice, Payment};
+use crate::Result;
+
+/// Number of items requested per page unless specified otherwise.
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Item which can be used as a pagination cursor.
+pub(crate) trait Paged {
+    fn timestamp(&self) -> DateTime<Utc>;
+    fn id(&self) -> &str;
+}
+
+impl Paged for Allocation {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn id(&self) -> &str {
+        &self.allocation_id
+    }
+}
+
+impl Paged for DebitNote {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn id(&self) -> &str {
+        &self.debit_note_id
+    }
+}
+
+impl Paged for Invoice {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn id(&self) -> &str {
+        &self.invoice_id
+    }
+}
+
+impl Paged for Payment {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn id(&self) -> &str {
+        &self.payment_id
+    }
+}
+
+impl Paged for Signed<Payment> {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.payload.timestamp
+    }
+
+    fn id(&self) -> &str {
+        &self.payload.payment_id
+    }
+}
+
+struct Pager<F> {
+    fetch: F,
+    cursor: Option<DateTime<Utc>>,
+    page_size: u32,
+    limit: u32,
+    seen: HashSet<String>,
+}
+
+impl<T, F, Fut> Pager<F>
+where
+    T: Paged,
+    F: FnMut(Option<DateTime<Utc>>, u32) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    /// Fetches next page and returns its unseen items
+    /// together with the flag whether there may be more pages.
+    async fn next_page(&mut self) -> Result<(Vec<T>, bool)> {
+        let items = (self.fetch)(self.cursor, self.limit).await?;
+        let is_full = items.len() >= self.limit as usize;
+
+        let last_ts = items.iter().map(Paged::timestamp).max();
+        let prev_ts = last_ts.and_then(|last_ts| {
+            items
+                .iter()
+                .map(Paged::timestamp)
+                .filter(|ts| *ts < last_ts)
+                .max()
+        });
+
+        // Items sharing the timestamp of the last one may not fit into this page.
+        // They will be fetched again, so remember which of them were already returned.
+        let boundary = items
+            .iter()
+            .filter(|item| Some(item.timestamp()) == last_ts)
+            .map(|item| item.id().to_string())
+            .collect::<HashSet<_>>();
+
+        let items = items
+            .into_iter()
+            .filter(|item| !self.seen.contains(item.id()))
+            .collect();
+
+        match prev_ts {
+            Some(prev_ts) if is_full => {
+                self.cursor = Some(prev_ts);
+                self.limit = self.page_size;
+                self.seen = boundary;
+            }
+            // The whole page shares a single timestamp,
+            // so the cursor cannot advance until the page grows.
+            None if is_full => {
+                self.limit = self.limit.saturating_mul(2);
+                self.seen.extend(boundary);
+            }
+            _ => (),
+        }
+
+        Ok((items, is_full))
+    }
+}
+
+/// Streams items of a list endpoint, page by page.
+///
+/// `fetch` is called with the `afterTimestamp` cursor and the maximum number of items
+/// to return. Pagination stops after the first page which is not full or on the first error.
+pub(crate) fn paginate<T, F, Fut>(
+    after_timestamp: Option<DateTime<Utc>>,
+    page_size: Option<u32>,
+    fetch: F,
+) -> impl Stream<Item = Result<T>>
+where
+    T: Paged,
+    F: FnMut(Option<DateTime<Utc>>, u32) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let pager = Pager {
+        fetch,
+        cursor: after_timestamp,
+        page_size,
+        limit: page_size,
+        seen: HashSet::new(),
+    };
+
+    let pages = stream::unfold(Some(pager), |pager| async move {
+        let mut pager = pager?;
+        match pager.next_page().await {
+            Ok((items, true)) => Some((Ok(items), Some(pager))),
+            Ok((items, false)) => Some((Ok(items), None)),
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    flatten_pages(pages)
+}
+
+/// Streams items of an endpoint which returns all of them at once.
+pub(crate) fn single_page<T, Fut>(fetch: Fut) -> impl Stream<Item = Result<T>>
+where
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    flatten_pages(stream::once(fetch))
+}
+
+fn flatten_pages<T>(pages: impl Stream<Item = Result<Vec<T>>>) -> impl Stream<Item = Result<T>> {
+    pages.flat_map(|page| match page {
+        Ok(items) => stream::iter(items.into_iter().map(Ok)).left_stream(),
+        Err(e) => stream::once(ready(Err(e))).right_stream(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use chrono::TimeZone;
+    use std::cell::RefCell;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Item(DateTime<Utc>, String);
+
+    impl Paged for Item {
+        fn timestamp(&self) -> DateTime<Utc> {
+            self.0
+        }
+
+        fn id(&self) -> &str {
+            &self.1
+        }
+    }
+
+    fn items(timestamps: &[i64]) -> Vec<Item> {
+        timestamps
+            .iter()
+            .enumerate()
+            .map(|(i, ts)| Item(Utc.timestamp_opt(*ts, 0).unwrap(), i.to_string()))
+            .collect()
+    }
+
+    async fn collect_all(src: &[Item], page_size: u32) -> (Vec<Item>, usize) {
+        let calls = RefCell::new(0);
+        let result = paginate(None, Some(page_size), |after, limit| {
+            *calls.borrow_mut() += 1;
+            let page = src
+                .iter()
+                .filter(|item| after.map(|ts| item.0 > ts).unwrap_or(true))
+                .take(limit as usize)
+                .cloned()
+                .collect::<Vec<_>>();
+            ready(Ok::<_, Error>(page))
+        })
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+        let calls = calls.into_inner();
+        (result, calls)
+    }
+
+    #[actix_rt::test]
+    async fn distinct_timestamps() {
+        let src = items(&[1, 2, 3, 4, 5, 6, 7]);
+        for page_size in 1..10 {
+            assert_eq!(collect_all(&src, page_size).await.0, src);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn identical_timestamps_at_page_boundary() {
+        let src = items(&[1, 2, 2, 2, 3, 3, 4, 4, 4, 4, 4, 5]);
+        for page_size in 1..15 {
+            assert_eq!(collect_all(&src, page_size).await.0, src);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn stops_on_partial_page() {
+        let src = items(&[1, 2, 3, 4, 5]);
+        assert_eq!(collect_all(&src, 3).await.1, 3);
+        assert_eq!(collect_all(&src, 6).await.1, 1);
+        assert_eq!(collect_all(&[], 5).await.1, 1);
+    }
+
+    #[actix_rt::test]
+    async fn stops_on_error() {
+        let result = paginate(None, Some(2), |_, _| {
+            ready(Err::<Vec<Item>, _>(Error::InternalError("boom".into())))
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_err());
+    }
+}
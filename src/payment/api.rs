@@ -6,14 +6,17 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 
 use crate::{
-    web::{default_on_timeout, url_format_obj, WebClient, WebInterface},
+    paging::paginate,
+    web::{default_on_timeout, timeout_secs, url_format_obj, ApiEvent, WebClient, WebInterface},
     Result,
 };
+use futures::Stream;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use ya_client_model::payment::payment::Signed;
 use ya_client_model::payment::*;
+use ya_client_model::{AllocationId, DebitNoteId, InvoiceId};
 
 #[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
@@ -101,21 +104,45 @@ impl PaymentApi {
         self.client.get(&url).send().json().await
     }
 
-    pub async fn get_allocation(&self, allocation_id: &str) -> Result<Allocation> {
+    /// Streams all allocations created after given timestamp, fetching them page by page.
+    pub fn get_allocations_stream<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<Allocation>>
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let api = self.clone();
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        paginate(after_timestamp, page_size, move |after, limit| {
+            let api = api.clone();
+            async move { api.get_allocations(after, Some(limit)).await }
+        })
+    }
+
+    pub async fn get_allocation(
+        &self,
+        allocation_id: impl Into<AllocationId>,
+    ) -> Result<Allocation> {
+        let allocation_id = allocation_id.into();
         let url = url_format!("allocations/{allocation_id}");
         self.client.get(&url).send().json().await
     }
 
     pub async fn amend_allocation(
         &self,
-        allocation_id: &str,
+        allocation_id: impl Into<AllocationId>,
         allocation: &AllocationUpdate,
     ) -> Result<Allocation> {
+        let allocation_id = allocation_id.into();
         let url = url_format!("allocations/{allocation_id}");
         self.client.put(&url).send_json(allocation).json().await
     }
 
-    pub async fn release_allocation(&self, allocation_id: &str) -> Result<()> {
+    pub async fn release_allocation(&self, allocation_id: impl Into<AllocationId>) -> Result<()> {
+        let allocation_id = allocation_id.into();
         let url = url_format!("allocations/{allocation_id}");
         self.client.delete(&url).send().json().await
     }
@@ -154,14 +181,33 @@ impl PaymentApi {
         self.client.get(&url).send().json().await
     }
 
-    pub async fn get_debit_note(&self, debit_note_id: &str) -> Result<DebitNote> {
+    /// Streams all debit notes issued after given timestamp, fetching them page by page.
+    pub fn get_debit_notes_stream<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<DebitNote>>
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let api = self.clone();
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        paginate(after_timestamp, page_size, move |after, limit| {
+            let api = api.clone();
+            async move { api.get_debit_notes(after, Some(limit)).await }
+        })
+    }
+
+    pub async fn get_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> Result<DebitNote> {
+        let debit_note_id = debit_note_id.into();
         let url = url_format!("debitNotes/{debit_note_id}");
         self.client.get(&url).send().json().await
     }
 
     pub async fn get_payments_for_debit_note<Tz>(
         &self,
-        debit_note_id: &str,
+        debit_note_id: impl Into<DebitNoteId>,
         after_timestamp: Option<DateTime<Tz>>,
         max_items: Option<u32>,
     ) -> Result<Vec<Payment>>
@@ -169,6 +215,7 @@ impl PaymentApi {
         Tz: TimeZone,
         Tz::Offset: Display,
     {
+        let debit_note_id = debit_note_id.into();
         // NOT IMPLEMENTED ON SERVER
         let input = params::FilterParams {
             after_timestamp: after_timestamp.map(|dt| dt.with_timezone(&Utc)),
@@ -216,7 +263,7 @@ impl PaymentApi {
     ///     }
     /// }
     /// ```
-    pub fn events<Evtype: PaymentEvent>(&self) -> EventsBuilder<Evtype> {
+    pub fn events<Evtype: PaymentEvent>(&self) -> EventsBuilder<'_, Evtype> {
         EventsBuilder::with_client(&self.client)
     }
 
@@ -240,8 +287,9 @@ impl PaymentApi {
         let url = url_format_obj("debitNoteEvents", &input);
         self.client
             .get(&url)
+            .long_poll(timeout)
             .send()
-            .json()
+            .events()
             .await
             .or_else(default_on_timeout)
     }
@@ -257,22 +305,34 @@ impl PaymentApi {
             .await
     }
 
-    pub async fn send_debit_note(&self, debit_note_id: &str) -> Result<()> {
+    pub async fn send_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> Result<()> {
+        let debit_note_id = debit_note_id.into();
         let input = params::Timeout {
             timeout: self.config.send_debit_note_timeout,
         };
         let base_url = format!("debitNotes/{}/send", debit_note_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.send_debit_note_timeout))
+            .send()
+            .json()
+            .await
     }
 
-    pub async fn cancel_debit_note(&self, debit_note_id: &str) -> Result<()> {
+    pub async fn cancel_debit_note(&self, debit_note_id: impl Into<DebitNoteId>) -> Result<()> {
+        let debit_note_id = debit_note_id.into();
         let input = params::Timeout {
             timeout: self.config.cancel_debit_note_timeout,
         };
         let base_url = format!("debitNotes/{}/cancel", debit_note_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.cancel_debit_note_timeout))
+            .send()
+            .json()
+            .await
     }
 
     // debit_notes
@@ -280,28 +340,40 @@ impl PaymentApi {
 
     pub async fn accept_debit_note(
         &self,
-        debit_note_id: &str,
+        debit_note_id: impl Into<DebitNoteId>,
         acceptance: &Acceptance,
     ) -> Result<()> {
+        let debit_note_id = debit_note_id.into();
         let input = params::Timeout {
             timeout: self.config.accept_debit_note_timeout,
         };
         let base_url = format!("debitNotes/{}/accept", debit_note_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send_json(acceptance).json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.accept_debit_note_timeout))
+            .send_json(acceptance)
+            .json()
+            .await
     }
 
     pub async fn reject_debit_note(
         &self,
-        debit_note_id: &str,
+        debit_note_id: impl Into<DebitNoteId>,
         rejection: &Rejection,
     ) -> Result<()> {
+        let debit_note_id = debit_note_id.into();
         let input = params::Timeout {
             timeout: self.config.reject_debit_note_timeout,
         };
         let base_url = format!("debitNotes/{}/reject", debit_note_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send_json(rejection).json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.reject_debit_note_timeout))
+            .send_json(rejection)
+            .json()
+            .await
     }
 
     // invoices
@@ -324,14 +396,33 @@ impl PaymentApi {
         self.client.get(&url).send().json().await
     }
 
-    pub async fn get_invoice(&self, invoice_id: &str) -> Result<Invoice> {
+    /// Streams all invoices issued after given timestamp, fetching them page by page.
+    pub fn get_invoices_stream<Tz>(
+        &self,
+        after_timestamp: Option<DateTime<Tz>>,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<Invoice>>
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let api = self.clone();
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        paginate(after_timestamp, page_size, move |after, limit| {
+            let api = api.clone();
+            async move { api.get_invoices(after, Some(limit)).await }
+        })
+    }
+
+    pub async fn get_invoice(&self, invoice_id: impl Into<InvoiceId>) -> Result<Invoice> {
+        let invoice_id = invoice_id.into();
         let url = url_format!("invoices/{invoice_id}");
         self.client.get(&url).send().json().await
     }
 
     pub async fn get_payments_for_invoice<Tz>(
         &self,
-        invoice_id: &str,
+        invoice_id: impl Into<InvoiceId>,
         after_timestamp: Option<DateTime<Tz>>,
         max_items: Option<u32>,
     ) -> Result<Vec<Payment>>
@@ -339,6 +430,7 @@ impl PaymentApi {
         Tz: TimeZone,
         Tz::Offset: Display,
     {
+        let invoice_id = invoice_id.into();
         // NOT IMPLEMENTED ON SERVER
         let input = params::FilterParams {
             after_timestamp: after_timestamp.map(|dt| dt.with_timezone(&Utc)),
@@ -370,8 +462,9 @@ impl PaymentApi {
         let url = url_format_obj("invoiceEvents", &input);
         self.client
             .get(&url)
+            .long_poll(timeout)
             .send()
-            .json()
+            .events()
             .await
             .or_else(default_on_timeout)
     }
@@ -383,43 +476,75 @@ impl PaymentApi {
         self.client.post("invoices").send_json(invoice).json().await
     }
 
-    pub async fn send_invoice(&self, invoice_id: &str) -> Result<()> {
+    pub async fn send_invoice(&self, invoice_id: impl Into<InvoiceId>) -> Result<()> {
+        let invoice_id = invoice_id.into();
         let input = params::Timeout {
             timeout: self.config.send_invoice_timeout,
         };
         let base_url = format!("invoices/{}/send", invoice_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.send_invoice_timeout))
+            .send()
+            .json()
+            .await
     }
 
-    pub async fn cancel_invoice(&self, invoice_id: &str) -> Result<()> {
+    pub async fn cancel_invoice(&self, invoice_id: impl Into<InvoiceId>) -> Result<()> {
+        let invoice_id = invoice_id.into();
         let input = params::Timeout {
             timeout: self.config.cancel_invoice_timeout,
         };
         let base_url = format!("invoices/{}/cancel", invoice_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send().json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.cancel_invoice_timeout))
+            .send()
+            .json()
+            .await
     }
 
     // invoices
     // Requestor
 
-    pub async fn accept_invoice(&self, invoice_id: &str, acceptance: &Acceptance) -> Result<()> {
+    pub async fn accept_invoice(
+        &self,
+        invoice_id: impl Into<InvoiceId>,
+        acceptance: &Acceptance,
+    ) -> Result<()> {
+        let invoice_id = invoice_id.into();
         let input = params::Timeout {
             timeout: self.config.accept_invoice_timeout,
         };
         let base_url = format!("invoices/{}/accept", invoice_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send_json(acceptance).json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.accept_invoice_timeout))
+            .send_json(acceptance)
+            .json()
+            .await
     }
 
-    pub async fn reject_invoice(&self, invoice_id: &str, rejection: &Rejection) -> Result<()> {
+    pub async fn reject_invoice(
+        &self,
+        invoice_id: impl Into<InvoiceId>,
+        rejection: &Rejection,
+    ) -> Result<()> {
+        let invoice_id = invoice_id.into();
         let input = params::Timeout {
             timeout: self.config.reject_invoice_timeout,
         };
         let base_url = format!("invoices/{}/reject", invoice_id);
         let url = url_format_obj(&base_url, &input);
-        self.client.post(&url).send_json(rejection).json().await
+        self.client
+            .post(&url)
+            .long_poll(timeout_secs(self.config.reject_invoice_timeout))
+            .send_json(rejection)
+            .json()
+            .await
     }
 
     // payments
@@ -435,21 +560,39 @@ impl PaymentApi {
         Tz: TimeZone,
         Tz::Offset: Display,
     {
-        let input = params::EventParams {
-            after_timestamp: after_timestamp.map(|dt| dt.with_timezone(&Utc)),
-            timeout: timeout.map(|d| d.as_secs_f64()),
-            max_events,
-            app_session_id,
-        };
-        let url = url_format_obj("payments", &input);
-        self.client
-            .get(&url)
-            .send()
-            .json()
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        self.fetch_payments(after_timestamp, timeout, max_events, app_session_id)
             .await
             .or_else(default_on_timeout)
     }
 
+    /// Streams all payments made after given timestamp, fetching them page by page.
+    ///
+    /// Unlike [`get_payments`](#method.get_payments) it does not wait for new payments
+    /// and ends as soon as all existing ones are returned. A page which times out ends
+    /// the stream with [`Error::TimeoutError`](crate::Error::TimeoutError).
+    pub fn get_payments_stream<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        page_size: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> impl Stream<Item = Result<Payment>>
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let api = self.clone();
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        paginate(after_timestamp, page_size, move |after, limit| {
+            let api = api.clone();
+            let app_session_id = app_session_id.clone();
+            async move {
+                api.fetch_payments(after, Some(Duration::ZERO), Some(limit), app_session_id)
+                    .await
+            }
+        })
+    }
+
     pub async fn get_signed_payments<Tz>(
         &self,
         after_timestamp: Option<&DateTime<Tz>>,
@@ -461,19 +604,55 @@ impl PaymentApi {
         Tz: TimeZone,
         Tz::Offset: Display,
     {
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        self.fetch_payments(after_timestamp, timeout, max_events, app_session_id)
+            .await
+            .or_else(default_on_timeout)
+    }
+
+    /// Streams all signed payments made after given timestamp, fetching them page by page.
+    ///
+    /// Unlike [`get_signed_payments`](#method.get_signed_payments) it does not wait
+    /// for new payments and ends as soon as all existing ones are returned. A page which
+    /// times out ends the stream with [`Error::TimeoutError`](crate::Error::TimeoutError).
+    pub fn get_signed_payments_stream<Tz>(
+        &self,
+        after_timestamp: Option<&DateTime<Tz>>,
+        page_size: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> impl Stream<Item = Result<Signed<Payment>>>
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let api = self.clone();
+        let after_timestamp = after_timestamp.map(|dt| dt.with_timezone(&Utc));
+        paginate(after_timestamp, page_size, move |after, limit| {
+            let api = api.clone();
+            let app_session_id = app_session_id.clone();
+            async move {
+                api.fetch_payments(after, Some(Duration::ZERO), Some(limit), app_session_id)
+                    .await
+            }
+        })
+    }
+
+    /// Fetches payments, returning timeouts as errors instead of an empty list.
+    async fn fetch_payments<T: DeserializeOwned>(
+        &self,
+        after_timestamp: Option<DateTime<Utc>>,
+        timeout: Option<Duration>,
+        max_events: Option<u32>,
+        app_session_id: Option<String>,
+    ) -> Result<Vec<T>> {
         let input = params::EventParams {
-            after_timestamp: after_timestamp.map(|dt| dt.with_timezone(&Utc)),
+            after_timestamp,
             timeout: timeout.map(|d| d.as_secs_f64()),
             max_events,
             app_session_id,
         };
         let url = url_format_obj("payments", &input);
-        self.client
-            .get(&url)
-            .send()
-            .json()
-            .await
-            .or_else(default_on_timeout)
+        self.client.get(&url).long_poll(timeout).send().json().await
     }
 
     pub async fn get_payment(&self, payment_id: &str) -> Result<Payment> {
@@ -498,7 +677,7 @@ impl PaymentApi {
     }
 }
 
-pub trait PaymentEvent: DeserializeOwned {
+pub trait PaymentEvent: ApiEvent + DeserializeOwned {
     const PATH: &'static str;
     type EventType: ToString;
 }
@@ -596,7 +775,7 @@ impl<'a, EvType: PaymentEvent> EventsBuilder<'a, EvType> {
             app_session_id: self.app_session_id,
         };
         let url = url_format_obj(EvType::PATH, &input);
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).long_poll(self.timeout);
         if let Some(requestor_events) = self.requestor_events {
             req = req.add_header("X-Requestor-Events", requestor_events.as_str())
         }
@@ -604,6 +783,64 @@ impl<'a, EvType: PaymentEvent> EventsBuilder<'a, EvType> {
             req = req.add_header("X-Provider-Events", provider_events.as_str())
         }
 
-        req.send().json().await.or_else(default_on_timeout)
+        req.send().events().await.or_else(default_on_timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Response};
+    use crate::Error;
+    use futures::StreamExt;
+
+    fn payment(id: &str, timestamp: &str) -> String {
+        format!(
+            r#"{{"paymentId":"{id}","payerId":"0x0000000000000000000000000000000000000001",
+                "payeeId":"0x0000000000000000000000000000000000000002","payerAddr":"0x01",
+                "payeeAddr":"0x02","paymentPlatform":"erc20-holesky-tglm","amount":"1",
+                "timestamp":"{timestamp}","agreementPayments":[],"activityPayments":[],
+                "details":""}}"#
+        )
+    }
+
+    #[actix_rt::test]
+    async fn stream_fails_on_timed_out_page() {
+        let page = format!(
+            "[{},{}]",
+            payment("p1", "2024-01-01T00:00:01Z"),
+            payment("p2", "2024-01-01T00:00:02Z")
+        );
+        let mut responses = vec![
+            Response::json(page),
+            Response::status("408 Request Timeout"),
+            Response::status("408 Request Timeout"),
+        ]
+        .into_iter();
+        let (url, server) = testing::serve(3, move |_| responses.next().unwrap());
+        let client = WebClient::builder().api_url(url.parse().unwrap()).build();
+        let api = PaymentApi::from_client(client);
+
+        let result: Vec<_> = api
+            .get_payments_stream::<Utc>(None, Some(2), None)
+            .collect()
+            .await;
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].as_ref().unwrap().payment_id, "p1");
+        assert_eq!(result[1].as_ref().unwrap().payment_id, "p2");
+        assert!(matches!(result[2], Err(Error::TimeoutError { .. })));
+
+        // polling for new payments still treats a timeout as no payments
+        let payments = api
+            .get_payments::<Utc>(None, Some(Duration::from_secs(1)), None, None)
+            .await
+            .unwrap();
+        assert!(payments.is_empty());
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].line.contains("maxEvents=2"));
+        assert!(requests[1]
+            .line
+            .contains("afterTimestamp=2024-01-01T00%3A00%3A01Z"));
     }
 }
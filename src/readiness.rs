@@ -0,0 +1,239 @@
+//! Waiting for the Yagna daemon to become ready
+use awc::http::StatusCode;
+use futures::Future;
+use std::cmp::min;
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::activity::ActivityProviderApi;
+use crate::identity::IdentityApi;
+use crate::market::MarketRequestorApi;
+use crate::model::identity::Identity;
+use crate::model::version::Version;
+use crate::net::NetApi;
+use crate::payment::PaymentApi;
+use crate::version::VersionApi;
+use crate::web::{WebClient, WebInterface};
+use crate::{Error, Result};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Reason of a failed readiness check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckError {
+    /// The check was not performed, eg. because the daemon did not respond at all.
+    NotChecked,
+    /// The service could not be reached.
+    Unreachable(String),
+    /// The app key was rejected.
+    Unauthorized,
+    /// The service responded with an error.
+    Failed(String),
+}
+
+impl From<Error> for CheckError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::HttpError {
+                code: StatusCode::UNAUTHORIZED,
+                ..
+            } => CheckError::Unauthorized,
+            Error::SendRequestError { .. }
+            | Error::TimeoutError { .. }
+            | Error::CircuitOpen { .. } => CheckError::Unreachable(e.to_string()),
+            _ => CheckError::Failed(e.to_string()),
+        }
+    }
+}
+
+pub type Check<T> = std::result::Result<T, CheckError>;
+
+/// Result of checking a single service API.
+#[derive(Clone, Debug)]
+pub struct ServiceReport {
+    pub name: &'static str,
+    pub url: Option<Url>,
+    pub check: Check<()>,
+}
+
+/// Result of [`WebClient::wait_ready`].
+#[derive(Clone, Debug)]
+pub struct ReadinessReport {
+    /// Identity of the daemon; fails with [`CheckError::Unauthorized`] if the app key is rejected.
+    pub identity: Check<Identity>,
+    pub version: Check<Version>,
+    /// Market, Activity, Payment and Net API checks.
+    pub services: Vec<ServiceReport>,
+    pub attempts: u32,
+    pub elapsed: Duration,
+}
+
+impl ReadinessReport {
+    fn not_checked() -> Self {
+        ReadinessReport {
+            identity: Err(CheckError::NotChecked),
+            version: Err(CheckError::NotChecked),
+            services: Vec::new(),
+            attempts: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.identity.is_ok()
+            && self.version.is_ok()
+            && !self.services.is_empty()
+            && self.services.iter().all(|s| s.check.is_ok())
+    }
+
+    /// `false` if the daemon responded and rejected the app key.
+    pub fn is_app_key_accepted(&self) -> bool {
+        !matches!(self.identity, Err(CheckError::Unauthorized))
+    }
+}
+
+fn service<T>(name: &'static str, (url, check): (Option<Url>, Check<T>)) -> ServiceReport {
+    ServiceReport {
+        name,
+        url,
+        check: check.map(|_| ()),
+    }
+}
+
+impl WebClient {
+    /// Waits until the daemon accepts the app key and all service APIs respond.
+    ///
+    /// The daemon is polled with exponential backoff until it is ready,
+    /// the app key is rejected or `timeout` elapses.
+    /// Should be called on a client created with [`WebClientBuilder`](crate::web::WebClientBuilder),
+    /// since service URLs are resolved the same way as in [`WebClient::interface`].
+    pub async fn wait_ready(&self, timeout: Duration) -> ReadinessReport {
+        let started = Instant::now();
+        let deadline = started + timeout;
+        let mut backoff = INITIAL_BACKOFF;
+        let mut report = ReadinessReport::not_checked();
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+            match tokio::time::timeout_at(deadline, self.check_ready()).await {
+                Ok(r) => report = r,
+                Err(_) => break,
+            }
+            if report.is_ready() || !report.is_app_key_accepted() {
+                break;
+            }
+            if Instant::now() + backoff >= deadline {
+                break;
+            }
+            log::debug!("yagna not ready yet, retrying in {:?}", backoff);
+            tokio::time::sleep(backoff).await;
+            backoff = min(backoff * 2, MAX_BACKOFF);
+        }
+
+        report.attempts = attempts;
+        report.elapsed = started.elapsed();
+        report
+    }
+
+    async fn check_ready(&self) -> ReadinessReport {
+        let identity = self.check_api(|api: IdentityApi| async move { api.me().await });
+        let identity = identity.await.1;
+        if identity.is_err() {
+            return ReadinessReport {
+                identity,
+                ..ReadinessReport::not_checked()
+            };
+        }
+
+        let (version, market, activity, payment, net) = futures::join!(
+            self.check_api(|api: VersionApi| async move { api.get().await }),
+            self.check_api(|api: MarketRequestorApi| async move { api.get_demands().await }),
+            self.check_api(|api: ActivityProviderApi| async move { api.get_activity_ids().await }),
+            self.check_api(|api: PaymentApi| async move { api.get_requestor_accounts().await }),
+            self.check_api(|api: NetApi| async move { api.get_status().await }),
+        );
+        ReadinessReport {
+            identity,
+            version: version.1,
+            services: vec![
+                service("market", market),
+                service("activity", activity),
+                service("payment", payment),
+                service("net", net),
+            ],
+            attempts: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    async fn check_api<T, R, F, Fut>(&self, f: F) -> (Option<Url>, Check<R>)
+    where
+        T: WebInterface,
+        F: FnOnce(T) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let url = T::rebase_service_url(self.base_url.clone())
+            .ok()
+            .map(|url| (*url).clone());
+        let check = match self.interface::<T>() {
+            Ok(api) => f(api).await.map_err(CheckError::from),
+            Err(e) => Err(CheckError::from(e)),
+        };
+        (url, check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use awc::http::Method;
+
+    #[test]
+    fn check_error_from_error() {
+        let http_error = |code| Error::HttpError {
+            code,
+            msg: String::new(),
+            method: Method::GET,
+            url: "me".into(),
+        };
+        assert_eq!(
+            CheckError::from(http_error(StatusCode::UNAUTHORIZED)),
+            CheckError::Unauthorized
+        );
+        assert!(matches!(
+            CheckError::from(http_error(StatusCode::NOT_FOUND)),
+            CheckError::Failed(_)
+        ));
+        assert!(matches!(
+            CheckError::from(Error::TimeoutError {
+                msg: String::new(),
+                method: Method::GET,
+                url: "me".into(),
+            }),
+            CheckError::Unreachable(_)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn daemon_not_running() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = WebClient::builder()
+            .api_url(format!("http://127.0.0.1:{}", port).parse().unwrap())
+            .build();
+
+        let report = client.wait_ready(Duration::from_millis(500)).await;
+        assert!(!report.is_ready());
+        assert!(report.is_app_key_accepted());
+        assert!(report.attempts > 1);
+        assert!(matches!(report.identity, Err(CheckError::Unreachable(_))));
+        assert_eq!(report.version, Err(CheckError::NotChecked));
+        assert!(report.services.is_empty());
+    }
+}
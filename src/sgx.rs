@@ -1,10 +1,16 @@
 use chrono::Duration;
 use graphene_sgx::sgx::{self, SgxMeasurement};
-use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+mod attestation;
+pub use attestation::{SgxAttestationError, SgxVerifier};
 
 const CONFIG: &str = include_str!("sgx_config.json");
 
+/// Environment variable pointing to the file [`SgxConfig::from_env`] reads.
+pub const SGX_CONFIG_ENV_VAR: &str = "YAGNA_SGX_CONFIG";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SgxConfigJson {
@@ -15,6 +21,22 @@ struct SgxConfigJson {
     pub max_evidence_age: i64, // in seconds
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum SgxConfigError {
+    #[error("Failed to read SGX config {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid SGX config: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid enclave measurement `{0}`")]
+    Measurement(String),
+    #[error("Invalid max evidence age: {0} s")]
+    MaxEvidenceAge(i64),
+}
+
+/// Requirements an enclave has to meet for a secure activity to be created.
 #[derive(Clone, Debug)]
 pub struct SgxConfig {
     pub enable_attestation: bool,
@@ -24,24 +46,177 @@ pub struct SgxConfig {
     pub max_evidence_age: Duration,
 }
 
-lazy_static! {
-    pub static ref SGX_CONFIG: SgxConfig = {
-        let json_cfg = match std::env::var("YAGNA_SGX_CONFIG") {
-            Ok(cfg) => String::from_utf8(std::fs::read(cfg).unwrap()).unwrap(),
-            Err(_) => CONFIG.to_owned(),
-        };
-        let cfg: SgxConfigJson = serde_json::from_str(&json_cfg).unwrap();
+impl SgxConfig {
+    /// Starts a configuration enforcing attestation, with no enclave allowed yet.
+    pub fn builder() -> SgxConfigBuilder {
+        SgxConfigBuilder::default()
+    }
+
+    /// Reads the configuration from the file pointed to by `YAGNA_SGX_CONFIG`,
+    /// falling back to the embedded one.
+    pub fn from_env() -> Result<Self, SgxConfigError> {
+        match std::env::var_os(SGX_CONFIG_ENV_VAR) {
+            Some(path) => Self::from_file(path),
+            None => Self::from_json(CONFIG),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SgxConfigError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|source| SgxConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SgxConfigError> {
+        let cfg: SgxConfigJson = serde_json::from_str(json)?;
         log::debug!("SGX config: {:?}", &cfg);
-        SgxConfig {
-            enable_attestation: cfg.enable_attestation,
-            exeunit_hashes: cfg
-                .exeunit_hashes
-                .into_iter()
-                .map(|hex| sgx::parse_measurement(&hex).unwrap())
-                .collect(),
-            allow_debug: cfg.allow_debug,
-            allow_outdated_tcb: cfg.allow_outdated_tcb,
-            max_evidence_age: Duration::seconds(cfg.max_evidence_age),
+        // `Duration::seconds` panics beyond `i64::MAX` milliseconds
+        if !(0..=i64::MAX / 1000).contains(&cfg.max_evidence_age) {
+            return Err(SgxConfigError::MaxEvidenceAge(cfg.max_evidence_age));
+        }
+        let mut builder = SgxConfig::builder()
+            .enable_attestation(cfg.enable_attestation)
+            .allow_debug(cfg.allow_debug)
+            .allow_outdated_tcb(cfg.allow_outdated_tcb)
+            .max_evidence_age(Duration::seconds(cfg.max_evidence_age));
+        for hash in &cfg.exeunit_hashes {
+            builder = builder.exeunit_hash(hash);
+        }
+        builder.build()
+    }
+}
+
+/// Builds an [`SgxConfig`].
+#[derive(Clone, Debug)]
+pub struct SgxConfigBuilder {
+    enable_attestation: bool,
+    exeunit_hashes: Vec<String>,
+    exeunit_measurements: Vec<SgxMeasurement>,
+    allow_debug: bool,
+    allow_outdated_tcb: bool,
+    max_evidence_age: Duration,
+}
+
+impl Default for SgxConfigBuilder {
+    fn default() -> Self {
+        SgxConfigBuilder {
+            enable_attestation: true,
+            exeunit_hashes: Vec::new(),
+            exeunit_measurements: Vec::new(),
+            allow_debug: false,
+            allow_outdated_tcb: false,
+            max_evidence_age: Duration::seconds(60),
+        }
+    }
+}
+
+impl SgxConfigBuilder {
+    pub fn enable_attestation(mut self, enable: bool) -> Self {
+        self.enable_attestation = enable;
+        self
+    }
+
+    /// Allows an enclave with the given hex encoded measurement, validated on [`build`](#method.build).
+    pub fn exeunit_hash(mut self, hash: &str) -> Self {
+        self.exeunit_hashes.push(hash.to_string());
+        self
+    }
+
+    /// Allows an enclave with the given measurement.
+    pub fn exeunit_measurement(mut self, measurement: SgxMeasurement) -> Self {
+        self.exeunit_measurements.push(measurement);
+        self
+    }
+
+    pub fn allow_debug(mut self, allow: bool) -> Self {
+        self.allow_debug = allow;
+        self
+    }
+
+    pub fn allow_outdated_tcb(mut self, allow: bool) -> Self {
+        self.allow_outdated_tcb = allow;
+        self
+    }
+
+    pub fn max_evidence_age(mut self, age: Duration) -> Self {
+        self.max_evidence_age = age;
+        self
+    }
+
+    pub fn build(self) -> Result<SgxConfig, SgxConfigError> {
+        let mut exeunit_hashes = self
+            .exeunit_hashes
+            .into_iter()
+            .map(|hash| {
+                sgx::parse_measurement(&hash).map_err(|_| SgxConfigError::Measurement(hash))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        exeunit_hashes.extend(self.exeunit_measurements);
+        Ok(SgxConfig {
+            enable_attestation: self.enable_attestation,
+            exeunit_hashes,
+            allow_debug: self.allow_debug,
+            allow_outdated_tcb: self.allow_outdated_tcb,
+            max_evidence_age: self.max_evidence_age,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "5edbb025714683961d4a2cb51b1d0a4ee8225a6ced167f29eb67f639313d9490";
+
+    #[test]
+    fn embedded_config() {
+        let config = SgxConfig::from_json(CONFIG).unwrap();
+        assert!(config.enable_attestation);
+        assert_eq!(
+            config.exeunit_hashes,
+            [sgx::parse_measurement(HASH).unwrap()]
+        );
+        assert_eq!(config.max_evidence_age, Duration::seconds(60));
+    }
+
+    #[test]
+    fn builder() {
+        let config = SgxConfig::builder()
+            .exeunit_hash(HASH)
+            .exeunit_measurement([1; 32])
+            .build()
+            .unwrap();
+        assert!(config.enable_attestation);
+        assert!(!config.allow_debug);
+        assert!(!config.allow_outdated_tcb);
+        assert_eq!(config.exeunit_hashes.len(), 2);
+        assert_eq!(config.exeunit_hashes[1], [1; 32]);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(matches!(
+            SgxConfig::builder().exeunit_hash("5edb").build(),
+            Err(SgxConfigError::Measurement(hash)) if hash == "5edb"
+        ));
+        assert!(matches!(
+            SgxConfig::from_json("{}"),
+            Err(SgxConfigError::Json(_))
+        ));
+        assert!(matches!(
+            SgxConfig::from_file("/nonexistent/sgx_config.json"),
+            Err(SgxConfigError::Read { .. })
+        ));
+        let mut json: serde_json::Value = serde_json::from_str(CONFIG).unwrap();
+        for age in [-1, i64::MAX / 1000 + 1, i64::MAX] {
+            json["maxEvidenceAge"] = age.into();
+            assert!(matches!(
+                SgxConfig::from_json(&json.to_string()),
+                Err(SgxConfigError::MaxEvidenceAge(a)) if a == age
+            ));
         }
-    };
+    }
 }
@@ -0,0 +1,342 @@
+//! Verification of SGX attestation evidence
+//!
+//! An enclave proves its identity with an IAS report signed by Intel. The report
+//! is checked against an [`SgxConfig`] without contacting any service, so recorded
+//! evidence can be verified as well, given the time it is verified at.
+use chrono::{DateTime, Duration, Utc};
+use graphene_sgx::sgx::{self, SgxMeasurement};
+use graphene_sgx::{
+    AttestationReport, AttestationResponse, AttestationResult, AttestationVerifier,
+};
+
+use super::SgxConfig;
+use crate::model::activity::SgxCredentials;
+
+/// Check of the attestation evidence which failed.
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum SgxAttestationError {
+    #[error("invalid IAS report: {0}")]
+    InvalidReport(String),
+    #[error("invalid IAS report signature")]
+    InvalidSignature,
+    #[error("invalid enclave quote status: {0}")]
+    InvalidQuoteStatus(String),
+    #[error("invalid enclave flags: {0}")]
+    InvalidFlags(String),
+    #[error("nonce mismatch: expected {expected}, got {actual:?}")]
+    NonceMismatch {
+        expected: String,
+        actual: Option<String>,
+    },
+    #[error("evidence from {timestamp} is older than {} s", max_age.num_seconds())]
+    EvidenceTooOld {
+        timestamp: DateTime<Utc>,
+        max_age: Duration,
+    },
+    #[error("enclave {0} is not allowed")]
+    UnknownEnclave(String),
+    #[error("invalid enclave hash `{0}`")]
+    InvalidEnclaveHash(String),
+    #[error("enclave hash mismatch: claimed {claimed}, attested {attested}")]
+    EnclaveHashMismatch { claimed: String, attested: String },
+    #[error("enclave runs in debug mode")]
+    DebugEnclave,
+    #[error("enclave platform is out of date")]
+    OutdatedTcb,
+    #[error("report data mismatch: {0}")]
+    ReportDataMismatch(String),
+}
+
+/// Verifies attestation evidence against the requirements of an [`SgxConfig`].
+///
+/// Attestation is verified regardless of [`SgxConfig::enable_attestation`].
+#[derive(Clone, Debug)]
+pub struct SgxVerifier<'a> {
+    config: &'a SgxConfig,
+    nonce: Option<String>,
+    data: Vec<u8>,
+    now: Option<DateTime<Utc>>,
+}
+
+impl<'a> SgxVerifier<'a> {
+    pub fn new(config: &'a SgxConfig) -> Self {
+        SgxVerifier {
+            config,
+            nonce: None,
+            data: Vec::new(),
+            now: None,
+        }
+    }
+
+    /// Requires the report to have been requested with the given nonce.
+    pub fn nonce(mut self, nonce: &str) -> Self {
+        self.nonce = Some(nonce.to_string());
+        self
+    }
+
+    /// Appends to the data the enclave is required to have reported, by its SHA512 hash.
+    pub fn data(mut self, data: &[u8]) -> Self {
+        self.data.extend_from_slice(data);
+        self
+    }
+
+    /// Checks the age of the evidence at the given time instead of the current one.
+    pub fn now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Verifies credentials of a secure activity running the given task package.
+    pub fn verify_credentials(
+        self,
+        credentials: &SgxCredentials,
+        task_package: &str,
+    ) -> Result<(), SgxAttestationError> {
+        self.data(&credentials.requestor_pub_key.serialize())
+            .data(&credentials.enclave_pub_key.serialize())
+            .data(task_package.as_bytes())
+            .verify_report(
+                &credentials.ias_report,
+                &credentials.ias_sig,
+                &credentials.enclave_hash,
+            )
+    }
+
+    /// Verifies an IAS report of the enclave claiming the given hex encoded measurement.
+    pub fn verify_report(
+        &self,
+        ias_report: &str,
+        ias_sig: &[u8],
+        enclave_hash: &str,
+    ) -> Result<(), SgxAttestationError> {
+        let evidence = AttestationResponse::new(ias_report.to_string(), ias_sig);
+        let report: AttestationReport = serde_json::from_str(ias_report)
+            .map_err(|e| SgxAttestationError::InvalidReport(e.to_string()))?;
+
+        match check(&evidence, |v| v) {
+            AttestationResult::Ok => (),
+            AttestationResult::InvalidIasReport(msg) if msg == "Invalid IAS signature" => {
+                return Err(SgxAttestationError::InvalidSignature)
+            }
+            AttestationResult::InvalidIasReport(msg) => {
+                return Err(SgxAttestationError::InvalidReport(msg))
+            }
+            AttestationResult::InvalidQuoteStatus(status) => {
+                return Err(SgxAttestationError::InvalidQuoteStatus(status))
+            }
+            AttestationResult::InvalidFlags(flags) => {
+                return Err(SgxAttestationError::InvalidFlags(flags))
+            }
+            result => return Err(SgxAttestationError::InvalidReport(format!("{:?}", result))),
+        }
+
+        if let Some(nonce) = &self.nonce {
+            if report.nonce.as_ref() != Some(nonce) {
+                return Err(SgxAttestationError::NonceMismatch {
+                    expected: nonce.clone(),
+                    actual: report.nonce,
+                });
+            }
+        }
+
+        // IAS timestamps are in UTC, without the time zone
+        let timestamp = DateTime::parse_from_rfc3339(&format!("{}Z", report.timestamp))
+            .map_err(|e| SgxAttestationError::InvalidReport(e.to_string()))?
+            .with_timezone(&Utc);
+        let max_age = self.config.max_evidence_age;
+        if timestamp + max_age < self.now.unwrap_or_else(Utc::now) {
+            return Err(SgxAttestationError::EvidenceTooOld { timestamp, max_age });
+        }
+
+        if let AttestationResult::InvalidMrEnclave(attested) = check(&evidence, |v| {
+            v.mr_enclave_list(&self.config.exeunit_hashes)
+        }) {
+            return Err(SgxAttestationError::UnknownEnclave(attested));
+        }
+
+        let claimed: SgxMeasurement = sgx::parse_measurement(enclave_hash)
+            .map_err(|_| SgxAttestationError::InvalidEnclaveHash(enclave_hash.to_string()))?;
+        if let AttestationResult::InvalidMrEnclave(attested) =
+            check(&evidence, |v| v.mr_enclave(claimed))
+        {
+            return Err(SgxAttestationError::EnclaveHashMismatch {
+                claimed: enclave_hash.to_string(),
+                attested,
+            });
+        }
+
+        if !self.config.allow_debug && !check(&evidence, |v| v.not_debug()).is_ok() {
+            return Err(SgxAttestationError::DebugEnclave);
+        }
+
+        if !self.config.allow_outdated_tcb && !check(&evidence, |v| v.not_outdated()).is_ok() {
+            return Err(SgxAttestationError::OutdatedTcb);
+        }
+
+        if !self.data.is_empty() {
+            if let AttestationResult::InvalidReportData(reported) =
+                check(&evidence, |v| v.data(&self.data))
+            {
+                return Err(SgxAttestationError::ReportDataMismatch(reported));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a single check of the evidence, so that its failure can be told apart.
+fn check(
+    evidence: &AttestationResponse,
+    f: impl FnOnce(AttestationVerifier) -> AttestationVerifier,
+) -> AttestationResult {
+    f(evidence.clone().verifier()).check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+    // Evidence of a debug enclave on an out of date platform, recorded by graphene-sgx
+    const IAS_REPORT: &str = include_str!("fixtures/ias_report.json");
+    const IAS_SIG: &str = include_str!("fixtures/ias_sig.hex");
+    const MR_ENCLAVE: &str = "89fd096f44e202bc30d7f515f5166ae5f7c53707ca02176cd07353ec329d004e";
+    const NONCE: &str = "some nonce";
+
+    fn config() -> SgxConfig {
+        SgxConfig::builder()
+            .exeunit_hash(MR_ENCLAVE)
+            .allow_debug(true)
+            .allow_outdated_tcb(true)
+            .max_evidence_age(Duration::seconds(60))
+            .build()
+            .unwrap()
+    }
+
+    fn verifier(config: &SgxConfig) -> SgxVerifier<'_> {
+        SgxVerifier::new(config)
+            .nonce(NONCE)
+            .data(&[0xde, 0xad, 0xc0, 0xde])
+            .data(&[0xca, 0xfe, 0xba, 0xbe])
+            .now("2020-10-06T08:53:00Z".parse().unwrap())
+    }
+
+    fn verify(verifier: &SgxVerifier) -> Result<(), SgxAttestationError> {
+        verifier.verify_report(IAS_REPORT, &hex::decode(IAS_SIG).unwrap(), MR_ENCLAVE)
+    }
+
+    #[test]
+    fn recorded_evidence() {
+        verify(&verifier(&config())).unwrap();
+    }
+
+    #[test]
+    fn invalid_report() {
+        let config = config();
+        let verifier = verifier(&config);
+        let mut sig = hex::decode(IAS_SIG).unwrap();
+        sig[0] ^= 1;
+        assert_eq!(
+            verifier.verify_report(IAS_REPORT, &sig, MR_ENCLAVE),
+            Err(SgxAttestationError::InvalidSignature)
+        );
+        let report = IAS_REPORT.replace("some nonce", "other nonce");
+        assert_eq!(
+            verifier.verify_report(&report, &hex::decode(IAS_SIG).unwrap(), MR_ENCLAVE),
+            Err(SgxAttestationError::InvalidSignature)
+        );
+        assert!(matches!(
+            verifier.verify_report("{}", &sig, MR_ENCLAVE),
+            Err(SgxAttestationError::InvalidReport(_))
+        ));
+    }
+
+    #[test]
+    fn nonce_and_age() {
+        let config = config();
+        assert_eq!(
+            verify(&verifier(&config).nonce("other nonce")),
+            Err(SgxAttestationError::NonceMismatch {
+                expected: "other nonce".into(),
+                actual: Some(NONCE.into()),
+            })
+        );
+        assert!(matches!(
+            verify(&verifier(&config).now("2020-10-06T08:54:00Z".parse().unwrap())),
+            Err(SgxAttestationError::EvidenceTooOld { max_age, .. }) if max_age == Duration::seconds(60)
+        ));
+        assert!(matches!(
+            verify(&SgxVerifier::new(&config)),
+            Err(SgxAttestationError::EvidenceTooOld { .. })
+        ));
+    }
+
+    #[test]
+    fn enclave_policy() {
+        let other = SgxConfig::builder()
+            .exeunit_measurement([1; 32])
+            .allow_debug(true)
+            .allow_outdated_tcb(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            verify(&verifier(&other)),
+            Err(SgxAttestationError::UnknownEnclave(MR_ENCLAVE.into()))
+        );
+
+        let config = config();
+        let claimed = "00".repeat(32);
+        assert_eq!(
+            verifier(&config).verify_report(IAS_REPORT, &hex::decode(IAS_SIG).unwrap(), &claimed),
+            Err(SgxAttestationError::EnclaveHashMismatch {
+                claimed,
+                attested: MR_ENCLAVE.into()
+            })
+        );
+        assert_eq!(
+            verifier(&config).verify_report(IAS_REPORT, &hex::decode(IAS_SIG).unwrap(), "89fd"),
+            Err(SgxAttestationError::InvalidEnclaveHash("89fd".into()))
+        );
+
+        let mut strict = config.clone();
+        strict.allow_debug = false;
+        assert_eq!(
+            verify(&verifier(&strict)),
+            Err(SgxAttestationError::DebugEnclave)
+        );
+        strict.allow_debug = true;
+        strict.allow_outdated_tcb = false;
+        assert_eq!(
+            verify(&verifier(&strict)),
+            Err(SgxAttestationError::OutdatedTcb)
+        );
+    }
+
+    #[test]
+    fn report_data() {
+        let config = config();
+        assert!(matches!(
+            verify(&verifier(&config).data(b"task")),
+            Err(SgxAttestationError::ReportDataMismatch(_))
+        ));
+
+        let secp = Secp256k1::new();
+        let key = |n| PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[n; 32]).unwrap());
+        let credentials = SgxCredentials {
+            enclave_pub_key: key(1),
+            requestor_pub_key: key(2),
+            payload_hash: String::new(),
+            enclave_hash: MR_ENCLAVE.into(),
+            ias_report: IAS_REPORT.into(),
+            ias_sig: hex::decode(IAS_SIG).unwrap(),
+        };
+        assert!(matches!(
+            SgxVerifier::new(&config)
+                .nonce(NONCE)
+                .now("2020-10-06T08:53:00Z".parse().unwrap())
+                .verify_credentials(&credentials, "hash:sha3:00:http://example.com"),
+            Err(SgxAttestationError::ReportDataMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+//! Servers answering requests of the client in unit tests
+use actix_codec::Framed;
+use awc::ws::{Codec, Frame, Message};
+use futures::{SinkExt, StreamExt};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Request received by the [`serve`] test server.
+#[derive(Clone, Debug)]
+pub(crate) struct Request {
+    /// Request line, eg. `GET /me HTTP/1.1`.
+    pub line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn read(stream: &mut TcpStream) -> Self {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let head_len = loop {
+            if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0, "connection closed before the end of request headers");
+            data.extend_from_slice(&buf[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&data[..head_len]).into_owned();
+        let mut lines = head.lines();
+        let line = lines.next().unwrap_or_default().to_string();
+        let headers: Vec<_> = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        let content_length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(0);
+        while data.len() < head_len + content_length {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0, "connection closed before the end of request body");
+            data.extend_from_slice(&buf[..n]);
+        }
+        let body = String::from_utf8_lossy(&data[head_len..]).into_owned();
+
+        Request {
+            line,
+            headers,
+            body,
+        }
+    }
+}
+
+/// Response sent by the [`serve`] test server.
+pub(crate) struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    /// `200 OK` response with a JSON body.
+    pub fn json(body: impl Into<String>) -> Self {
+        Response {
+            status: "200 OK",
+            content_type: "application/json",
+            body: body.into(),
+        }
+    }
+
+    /// `200 OK` response with a body of server-sent events.
+    pub fn events(body: impl Into<String>) -> Self {
+        Response {
+            status: "200 OK",
+            content_type: "text/event-stream",
+            body: body.into(),
+        }
+    }
+
+    /// Response with given status and an empty body.
+    pub fn status(status: &'static str) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: String::new(),
+        }
+    }
+
+    pub fn with_status(mut self, status: &'static str) -> Self {
+        self.status = status;
+        self
+    }
+
+    fn write(&self, stream: &mut TcpStream) {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+        .unwrap();
+    }
+}
+
+/// Serves `count` connections with responses returned by `respond`.
+///
+/// Returns the URL of the server and a handle joining to the received requests.
+pub(crate) fn serve(
+    count: usize,
+    mut respond: impl FnMut(&Request) -> Response + Send + 'static,
+) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        (0..count)
+            .map(|_| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let request = Request::read(&mut stream);
+                respond(&request).write(&mut stream);
+                request
+            })
+            .collect()
+    });
+    (url, handle)
+}
+
+/// Accepts a single WebSocket connection on the current thread, echoing binary frames back.
+pub(crate) async fn ws_echo_server() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::task::spawn_local(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = BufReader::new(socket);
+        let mut key = String::new();
+        loop {
+            let mut line = String::new();
+            socket.read_line(&mut line).await.unwrap();
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("sec-websocket-key") {
+                    key = value.trim().to_string();
+                }
+            }
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let accept = actix_http::ws::hash_key(key.as_bytes());
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nupgrade: websocket\r\n\
+             connection: upgrade\r\nsec-websocket-accept: {}\r\n\r\n",
+            std::str::from_utf8(&accept).unwrap()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+
+        let mut framed = Framed::new(socket.into_inner(), Codec::new());
+        while let Some(Ok(Frame::Binary(data))) = framed.next().await {
+            framed.send(Message::Binary(data)).await.unwrap();
+        }
+    });
+    addr
+}
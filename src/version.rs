@@ -0,0 +1,28 @@
+use ya_client_model::version::{Version, VERSION_API_PATH};
+
+use crate::web::{WebClient, WebInterface};
+use crate::Result;
+
+pub const VERSION_URL_ENV_VAR: &str = "YAGNA_VERSION_URL";
+
+/// Bindings for the Version API.
+#[derive(Clone)]
+pub struct VersionApi {
+    client: WebClient,
+}
+
+impl WebInterface for VersionApi {
+    const API_URL_ENV_VAR: &'static str = VERSION_URL_ENV_VAR;
+    const API_SUFFIX: &'static str = VERSION_API_PATH;
+
+    fn from_client(client: WebClient) -> Self {
+        VersionApi { client }
+    }
+}
+
+impl VersionApi {
+    /// Fetches the version of Yagna.
+    pub async fn get(&self) -> Result<Version> {
+        self.client.get("get").send().json().await
+    }
+}
@@ -1,31 +1,54 @@
 //! Web utils
-use actix_codec::Framed;
 use awc::{
     error::{PayloadError, SendRequestError},
     http::header::{HeaderMap, HeaderName, HeaderValue},
     http::{header, Method, StatusCode},
-    ws::Codec,
-    BoxedSocket, ClientRequest, ClientResponse, SendClientRequest,
+    ClientRequest, ClientResponse, SendClientRequest,
 };
 use bytes::{Bytes, BytesMut};
-use futures::stream::Peekable;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::future::{select, Either, LocalBoxFuture};
+use futures::stream::{LocalBoxStream, Peekable};
+use futures::{ready, Future, FutureExt, Stream, StreamExt, TryFutureExt, TryStreamExt};
 use heck::ToLowerCamelCase;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_qs;
 use std::cmp::max;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::{env, rc::Rc, str::FromStr, time::Duration};
+use tokio::time::Instant;
+pub use tokio_util::sync::CancellationToken;
 use url::{form_urlencoded, Url};
 
 use crate::model::ErrorMessage;
 use crate::{Error, Result};
 
+mod breaker;
+mod events;
+pub mod hooks;
+mod limit;
+mod socket;
+
+use breaker::Breaker;
+pub use breaker::{CircuitBreaker, CircuitState};
+pub use events::{ApiEvent, UnknownEvents};
+pub use hooks::Hook;
+use hooks::Hooks;
+pub use limit::Limits;
+use limit::{ClientLimiters, LimitGuard, LimiterChain};
+pub use socket::WebSocket;
+
 pub const YAGNA_API_URL_ENV_VAR: &str = "YAGNA_API_URL";
 pub const DEFAULT_YAGNA_API_URL: &str = "http://127.0.0.1:7465";
-const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+/// Default limit for the size of a response body read into memory.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+/// Time added to the server-side timeout of a long poll to get its HTTP timeout.
+pub const LONG_POLL_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+/// Server-side timeout of a long poll the daemon uses when none is given.
+pub const DEFAULT_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub fn rest_api_url() -> Url {
     let api_url = env::var(YAGNA_API_URL_ENV_VAR).unwrap_or(DEFAULT_YAGNA_API_URL.into());
@@ -43,8 +66,17 @@ pub enum WebAuth {
 /// https://docs.rs/awc/1.0/awc/struct.Client.html) with builder.
 #[derive(Clone)]
 pub struct WebClient {
-    base_url: Rc<Url>,
+    pub(crate) base_url: Rc<Url>,
     awc: awc::Client,
+    max_body_size: usize,
+    timeout: Option<Duration>,
+    limiters: Rc<ClientLimiters>,
+    limits: LimiterChain,
+    breaker: Option<Rc<Breaker>>,
+    cancel: Option<CancellationToken>,
+    hooks: Hooks,
+    unknown_events: UnknownEvents,
+    auth: Option<WebAuth>,
 }
 
 pub trait WebInterface {
@@ -86,11 +118,90 @@ impl WebRequestMeta {
     fn as_response_err(&self, code: StatusCode, msg: String) -> Error {
         Error::from_response(code, msg, self.method.clone(), self.url.clone())
     }
+
+    fn as_circuit_open_err(&self) -> Error {
+        Error::CircuitOpen {
+            method: self.method.clone(),
+            url: self.url.clone(),
+        }
+    }
+
+    fn as_limits_err(&self) -> Error {
+        Error::TimeoutError {
+            msg: "request queued by client-side limits for too long".to_string(),
+            method: self.method.clone(),
+            url: self.url.clone(),
+        }
+    }
+
+    fn as_cancelled_err(&self) -> Error {
+        Error::Cancelled {
+            method: self.method.clone(),
+            url: self.url.clone(),
+        }
+    }
+}
+
+/// Resolves once `cancel` is cancelled, never if there is none.
+fn cancelled(cancel: Option<CancellationToken>) -> LocalBoxFuture<'static, ()> {
+    match cancel {
+        Some(cancel) => async move { cancel.cancelled().await }.boxed_local(),
+        None => futures::future::pending().boxed_local(),
+    }
+}
+
+/// Resolves `future` unless `cancel` is cancelled first.
+///
+/// Dropping the request future closes its connection,
+/// so a cancelled request does not hold on to it.
+async fn cancellable<T>(
+    cancel: Option<CancellationToken>,
+    on_cancel: impl FnOnce() -> Error,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let cancel = match cancel {
+        Some(cancel) => cancel,
+        None => return future.await,
+    };
+    futures::pin_mut!(future);
+    let cancelled = cancel.cancelled();
+    futures::pin_mut!(cancelled);
+    match select(future, cancelled).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(on_cancel()),
+    }
 }
 
 pub struct WebRequest<T> {
     inner_request: T,
     meta: WebRequestMeta,
+    max_body_size: usize,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    limits: LimiterChain,
+    breaker: Option<Rc<Breaker>>,
+    cancel: Option<CancellationToken>,
+    hooks: Hooks,
+    rejected: Option<Error>,
+    unknown_events: UnknownEvents,
+}
+
+impl<T> WebRequest<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> WebRequest<U> {
+        WebRequest {
+            inner_request: f(self.inner_request),
+            meta: self.meta,
+            max_body_size: self.max_body_size,
+            timeout: self.timeout,
+            deadline: self.deadline,
+            limits: self.limits,
+            breaker: self.breaker,
+            cancel: self.cancel,
+            hooks: self.hooks,
+            rejected: self.rejected,
+            unknown_events: self.unknown_events,
+        }
+    }
 }
 
 impl WebClient {
@@ -109,12 +220,68 @@ impl WebClient {
         Ok(self.base_url.join(suffix.as_ref())?)
     }
 
+    /// Returns a client sending requests with the given timeout instead of the one
+    /// set with [`WebClientBuilder::timeout`]. APIs created from it use it as well.
+    pub fn with_timeout(&self, timeout: Duration) -> WebClient {
+        WebClient {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose requests fail with [`Error::Cancelled`] once `cancel` is cancelled.
+    /// APIs created from it use it as well.
+    ///
+    /// This allows to abort in-flight long polls, eg. [`MarketRequestorApi::collect`](
+    /// crate::market::MarketRequestorApi::collect), from another task.
+    pub fn with_cancellation(&self, cancel: CancellationToken) -> WebClient {
+        WebClient {
+            cancel: Some(cancel),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client authenticating with the given app key instead of the token
+    /// set with [`WebClientBuilder::auth_token`]. APIs created from it use it as well.
+    ///
+    /// Requests are made on behalf of the identity the app key is tied to, which allows
+    /// to act as several identities of a single daemon.
+    pub fn with_app_key(&self, app_key: &str) -> WebClient {
+        WebClient {
+            auth: Some(WebAuth::Bearer(app_key.to_string())),
+            ..self.clone()
+        }
+    }
+
+    /// Value of the `Authorization` header overriding the one of the underlying client.
+    fn auth_header(&self) -> Option<String> {
+        self.auth.as_ref().map(|auth| match auth {
+            WebAuth::Bearer(token) => format!("Bearer {token}"),
+        })
+    }
+
     pub fn request(&self, method: Method, url: &str) -> WebRequest<ClientRequest> {
         let url = self.url(url).unwrap().to_string();
         log::debug!("doing {} on {}", method, url);
+        let mut inner_request = self.awc.request(method.clone(), &url);
+        if let Some(timeout) = self.timeout {
+            inner_request = inner_request.timeout(timeout);
+        }
+        if let Some(auth) = self.auth_header() {
+            inner_request = inner_request.insert_header((header::AUTHORIZATION, auth));
+        }
         WebRequest {
-            inner_request: self.awc.request(method.clone(), &url),
+            inner_request,
             meta: WebRequestMeta::new(method, url),
+            max_body_size: self.max_body_size,
+            timeout: self.timeout,
+            deadline: None,
+            limits: self.limits.clone(),
+            breaker: self.breaker.clone(),
+            cancel: self.cancel.clone(),
+            hooks: self.hooks.clone(),
+            rejected: None,
+            unknown_events: self.unknown_events,
         }
     }
 
@@ -122,25 +289,86 @@ impl WebClient {
         let url = self.url(url).unwrap().to_string();
         log::debug!("event stream at {}", url);
         let method = Method::GET;
-        let request = self
+        let mut request = self
             .awc
             .request(method.clone(), &url)
             .insert_header((header::ACCEPT, mime::TEXT_EVENT_STREAM));
-        let stream = request
-            .send()
-            .await
-            .map_err(|e| Error::from_request(e, method, url))?
+        if let Some(auth) = self.auth_header() {
+            request = request.insert_header((header::AUTHORIZATION, auth));
+        }
+        self.hooks
+            .on_request(&method, &url, request.headers_mut(), None)?;
+        let meta = WebRequestMeta::new(method, url);
+        let response = cancellable(
+            self.cancel.clone(),
+            || meta.as_cancelled_err(),
+            request.send().map_err(|e| meta.as_request_err(e)),
+        )
+        .await
+        .map_err(|e| {
+            self.hooks.on_error(&meta.method, &meta.url, &e);
+            e
+        })?;
+        self.hooks.on_response(
+            &meta.method,
+            &meta.url,
+            response.status(),
+            response.headers(),
+        )?;
+        let hooks = self.hooks.clone();
+        let stream = response
             .into_stream()
+            .take_until(cancelled(self.cancel.clone()))
             .map_err(Error::from)
-            .event_stream();
+            .event_stream()
+            .and_then(move |event| futures::future::ready(hooks.on_event(&meta.url, event)));
         Ok(stream)
     }
 
-    pub async fn ws(&self, url: &str) -> Result<(ClientResponse, Framed<BoxedSocket, Codec>)> {
+    /// Opens a WebSocket connection, passing its messages and frames through the hooks.
+    pub async fn ws(&self, url: &str) -> Result<(ClientResponse, WebSocket)> {
         let mut url = self.base_url.join(url).unwrap();
         url.set_scheme("ws")
             .map_err(|_| Error::InternalError(format!("Invalid URL: {}", url)))?;
-        Ok(self.awc.ws(url.to_string()).connect().await?)
+        let meta = WebRequestMeta::new(Method::GET, url.to_string());
+        let mut headers = HeaderMap::new();
+        self.hooks
+            .on_request(&meta.method, &meta.url, &mut headers, None)?;
+        let mut request = headers
+            .iter()
+            .fold(self.awc.ws(meta.url.as_str()), |request, (name, value)| {
+                request.header(name.clone(), value.clone())
+            });
+        if let Some(auth) = self.auth_header() {
+            request = request.set_header(header::AUTHORIZATION, auth);
+        }
+        let (response, conn) = cancellable(
+            self.cancel.clone(),
+            || meta.as_cancelled_err(),
+            request.connect().map_err(Error::from),
+        )
+        .await
+        .map_err(|e| {
+            self.hooks.on_error(&meta.method, &meta.url, &e);
+            e
+        })?;
+        self.hooks.on_response(
+            &meta.method,
+            &meta.url,
+            response.status(),
+            response.headers(),
+        )?;
+        let conn = WebSocket::new(conn, self.hooks.clone(), meta.url);
+        Ok((response, conn))
+    }
+
+    /// Current state of the circuit breaker, if enabled.
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.breaker.as_ref().map(|breaker| breaker.state())
+    }
+
+    pub(crate) fn unknown_events(&self) -> UnknownEvents {
+        self.unknown_events
     }
 
     pub fn get(&self, url: &str) -> WebRequest<ClientRequest> {
@@ -169,32 +397,59 @@ impl WebClient {
             None => T::rebase_service_url(self.base_url.clone())?,
         };
 
-        let awc = self.awc.clone();
-        Ok(T::from_client(WebClient { base_url, awc }))
+        Ok(T::from_client(WebClient {
+            base_url,
+            awc: self.awc.clone(),
+            max_body_size: self.max_body_size,
+            timeout: self.timeout,
+            limiters: self.limiters.clone(),
+            limits: self.limiters.chain(Some(T::API_URL_ENV_VAR)),
+            breaker: self.breaker.clone(),
+            cancel: self.cancel.clone(),
+            hooks: self.hooks.clone(),
+            unknown_events: self.unknown_events,
+            auth: self.auth.clone(),
+        }))
     }
 }
 
 impl WebRequest<ClientRequest> {
+    /// Passes the request through the hooks before it is sent.
+    fn apply_hooks(mut self, body: Option<&mut Vec<u8>>) -> Self {
+        // awc counts the timeout from sending the request, so waiting for the
+        // limits uses up the same budget as receiving the response
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let headers = self.inner_request.headers_mut();
+        if let Err(e) = self
+            .hooks
+            .on_request(&self.meta.method, &self.meta.url, headers, body)
+        {
+            self.rejected.get_or_insert(e);
+        }
+        self
+    }
+
     pub fn send_json<T: Serialize + std::fmt::Debug>(
-        self,
+        mut self,
         value: &T,
     ) -> WebRequest<SendClientRequest> {
         log::trace!("sending payload: {:?}", value);
-        WebRequest {
-            inner_request: self.inner_request.send_json(value),
-            meta: self.meta,
-        }
+        // serialized here rather than by awc, so that hooks can see the body
+        let mut body = serde_json::to_vec(value).unwrap_or_else(|e| {
+            self.rejected = Some(e.into());
+            Vec::new()
+        });
+        self.inner_request = self
+            .inner_request
+            .insert_header_if_none((header::CONTENT_TYPE, mime::APPLICATION_JSON));
+        self.apply_hooks(Some(&mut body))
+            .map(|request| request.send_body(body))
     }
 
-    pub fn send_bytes(self, bytes: Vec<u8>) -> WebRequest<SendClientRequest> {
-        let inner_request = self
-            .inner_request
-            .content_type("application/octet-stream")
-            .send_body(bytes);
-        WebRequest {
-            inner_request,
-            meta: self.meta,
-        }
+    pub fn send_bytes(mut self, mut bytes: Vec<u8>) -> WebRequest<SendClientRequest> {
+        self.inner_request = self.inner_request.content_type("application/octet-stream");
+        self.apply_hooks(Some(&mut bytes))
+            .map(|request| request.send_body(bytes))
     }
 
     pub fn add_header(mut self, name: &str, value: &str) -> Self {
@@ -202,25 +457,82 @@ impl WebRequest<ClientRequest> {
         self
     }
 
-    pub fn send(self) -> WebRequest<SendClientRequest> {
-        WebRequest {
-            inner_request: self.inner_request.send(),
-            meta: self.meta,
+    /// Overrides the client timeout for this request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner_request = self.inner_request.timeout(timeout);
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Extends the client timeout, if needed, so that it does not cut short a long poll
+    /// waiting up to `server_timeout` on the server side, or [`DEFAULT_LONG_POLL_TIMEOUT`]
+    /// if not given.
+    ///
+    /// The timeout is extended to the server-side timeout plus [`LONG_POLL_TIMEOUT_MARGIN`].
+    pub fn long_poll(self, server_timeout: Option<Duration>) -> Self {
+        let server_timeout = server_timeout.unwrap_or(DEFAULT_LONG_POLL_TIMEOUT);
+        match self.timeout {
+            Some(timeout) if timeout < server_timeout + LONG_POLL_TIMEOUT_MARGIN => {
+                self.timeout(server_timeout + LONG_POLL_TIMEOUT_MARGIN)
+            }
+            _ => self,
         }
     }
+
+    /// Makes the request fail with [`Error::Cancelled`] once `cancel` is cancelled.
+    pub fn cancel_on(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn send(self) -> WebRequest<SendClientRequest> {
+        self.apply_hooks(None).map(|request| request.send())
+    }
 }
 
 impl WebRequest<SendClientRequest> {
+    async fn admit(&mut self) -> Result<LimitGuard> {
+        if let Some(e) = self.rejected.take() {
+            return Err(e);
+        }
+        if let Some(breaker) = &self.breaker {
+            if !breaker.admit().await {
+                return Err(self.meta.as_circuit_open_err());
+            }
+        }
+        let remaining = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        self.limits
+            .acquire(remaining)
+            .await
+            .ok_or_else(|| self.meta.as_limits_err())
+    }
+
     async fn request(
         self,
     ) -> Result<ClientResponse<impl Stream<Item = std::result::Result<Bytes, PayloadError>>>> {
         let meta = self.meta.clone();
-        let mut response = self
-            .inner_request
-            .await
-            .map_err(|e| meta.as_request_err(e))?;
+        let result = self.inner_request.await;
+        if let Some(breaker) = &self.breaker {
+            match result {
+                Ok(_) => breaker.record_success(),
+                Err(_) => breaker.record_failure(),
+            }
+        }
+        let mut response = result.map_err(|e| {
+            let e = meta.as_request_err(e);
+            self.hooks.on_error(&meta.method, &meta.url, &e);
+            e
+        })?;
 
         log::trace!("{:?}", response.headers());
+        self.hooks.on_response(
+            &meta.method,
+            &meta.url,
+            response.status(),
+            response.headers(),
+        )?;
         if response.status().is_success() {
             Ok(response)
         } else {
@@ -235,7 +547,7 @@ impl WebRequest<SendClientRequest> {
                     .map(|e: ErrorMessage| e.message.unwrap_or_default())
                     .unwrap_or_else(|e| format!("error parsing error msg: {}", e))
             } else {
-                match response.body().limit(MAX_BODY_SIZE).await {
+                match response.body().limit(self.max_body_size).await {
                     Ok(ref bytes) => String::from_utf8_lossy(bytes).to_string(),
                     Err(e) => e.to_string(),
                 }
@@ -245,11 +557,78 @@ impl WebRequest<SendClientRequest> {
     }
 
     pub async fn bytes(self) -> Result<Vec<u8>> {
-        Ok(self.request().await?.body().await?.to_vec())
+        let (cancel, meta) = (self.cancel.clone(), self.meta.clone());
+        cancellable(cancel, || meta.as_cancelled_err(), self.read_bytes()).await
+    }
+
+    async fn read_bytes(mut self) -> Result<Vec<u8>> {
+        let _guard = self.admit().await?;
+        let (meta, hooks) = (self.meta.clone(), self.hooks.clone());
+        let max_body_size = self.max_body_size;
+        let mut response = self.request().await?;
+        let body = response.body().limit(max_body_size).await?;
+        let body = hooks.on_response_body(
+            &meta.method,
+            &meta.url,
+            response.status(),
+            response.headers(),
+            body,
+        )?;
+        Ok(body.to_vec())
+    }
+
+    /// Yields the response body chunk by chunk, as it arrives.
+    ///
+    /// Unlike [`bytes`](#method.bytes) the body size is not limited.
+    ///
+    /// The stream ends early if the request is cancelled.
+    pub async fn stream(mut self) -> Result<LocalBoxStream<'static, Result<Bytes>>> {
+        let (cancel, meta) = (self.cancel.clone(), self.meta.clone());
+        let hooks = self.hooks.clone();
+        let (guard, response) = cancellable(cancel.clone(), || meta.as_cancelled_err(), async {
+            let guard = self.admit().await?;
+            Ok((guard, self.request().await?))
+        })
+        .await?;
+        let (status, headers) = (response.status(), response.headers().clone());
+        Ok(response
+            .take_until(cancelled(cancel))
+            .map_err(Error::from)
+            .map(move |chunk| {
+                // the request stays in-flight until the body is consumed
+                let _guard = &guard;
+                hooks.on_response_body(&meta.method, &meta.url, status, &headers, chunk?)
+            })
+            .boxed_local())
+    }
+
+    /// Deserializes a response holding a JSON array element by element.
+    ///
+    /// Unlike [`json`](#method.json) the body size is not limited, since
+    /// only a single array element is kept in memory at a time.
+    pub async fn json_stream<T: DeserializeOwned>(
+        self,
+    ) -> Result<JsonArrayStream<LocalBoxStream<'static, Result<Bytes>>, Error, T>> {
+        Ok(JsonArrayStream::new(self.stream().await?))
     }
 
     pub async fn json<T: DeserializeOwned>(self) -> Result<T> {
-        let meta = self.meta.clone();
+        let (cancel, meta) = (self.cancel.clone(), self.meta.clone());
+        cancellable(cancel, || meta.as_cancelled_err(), self.read_json()).await
+    }
+
+    /// Reads a JSON array of events, handling events of unknown types
+    /// as configured with [`WebClientBuilder::unknown_events`].
+    pub async fn events<E: ApiEvent + DeserializeOwned>(self) -> Result<Vec<E>> {
+        let (unknown_events, url) = (self.unknown_events, self.meta.url.clone());
+        let events = self.json().await?;
+        Ok(unknown_events.apply(&url, events))
+    }
+
+    async fn read_json<T: DeserializeOwned>(mut self) -> Result<T> {
+        let _guard = self.admit().await?;
+        let (meta, hooks) = (self.meta.clone(), self.hooks.clone());
+        let max_body_size = self.max_body_size;
         let mut response = self.request().await?;
 
         // allow empty body and no content (204) to pass smoothly
@@ -262,7 +641,14 @@ impl WebRequest<SendClientRequest> {
         {
             return Ok(serde_json::from_value(serde_json::json!(()))?);
         }
-        let raw_body = response.body().limit(MAX_BODY_SIZE).await?;
+        let raw_body = response.body().limit(max_body_size).await?;
+        let raw_body = hooks.on_response_body(
+            &meta.method,
+            &meta.url,
+            response.status(),
+            response.headers(),
+            raw_body,
+        )?;
         let body = std::str::from_utf8(&raw_body)?;
         log::debug!(
             "WebRequest.json(). method={} url={}, resp='{}'",
@@ -274,6 +660,11 @@ impl WebRequest<SendClientRequest> {
     }
 }
 
+/// Converts a server-side timeout given in seconds, for [`WebRequest::long_poll`].
+pub(crate) fn timeout_secs(secs: Option<impl Into<f64>>) -> Option<Duration> {
+    secs.and_then(|secs| Duration::try_from_secs_f64(secs.into()).ok())
+}
+
 // this is used internally to translate from HTTP Timeout into default result
 // (empty vec most of the time)
 pub(crate) fn default_on_timeout<T: Default>(err: Error) -> Result<T> {
@@ -292,6 +683,12 @@ pub struct WebClientBuilder {
     pub(crate) auth: Option<WebAuth>,
     pub(crate) headers: HeaderMap,
     pub(crate) timeout: Option<Duration>,
+    pub(crate) max_body_size: Option<usize>,
+    pub(crate) limits: Limits,
+    pub(crate) api_limits: HashMap<&'static str, Limits>,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+    pub(crate) hooks: Hooks,
+    pub(crate) unknown_events: UnknownEvents,
 }
 
 impl WebClientBuilder {
@@ -310,6 +707,53 @@ impl WebClientBuilder {
         self
     }
 
+    /// Limits the size of response bodies read into memory.
+    ///
+    /// Defaults to [`DEFAULT_MAX_BODY_SIZE`]. Streaming accessors like
+    /// [`WebRequest::json_stream`] are not affected.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Limits the rate and concurrency of requests sent by all APIs of the client.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Limits the rate and concurrency of requests sent by the given API,
+    /// on top of the limits set with [`limits`](#method.limits).
+    ///
+    /// Limits are shared by APIs configured with the same URL environment variable,
+    /// eg. [`MarketRequestorApi`](crate::market::MarketRequestorApi) and
+    /// [`MarketProviderApi`](crate::market::MarketProviderApi).
+    pub fn api_limits<T: WebInterface>(mut self, limits: Limits) -> Self {
+        self.api_limits.insert(T::API_URL_ENV_VAR, limits);
+        self
+    }
+
+    /// Enables the circuit breaker shared by all APIs of the client.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Registers a hook invoked with every request sent by all APIs
+    /// of the client and with its response.
+    pub fn hook(mut self, hook: impl Hook + 'static) -> Self {
+        self.hooks.push(Rc::new(hook));
+        self
+    }
+
+    /// Sets what methods collecting events do with events of unknown types.
+    ///
+    /// Defaults to [`UnknownEvents::Skip`].
+    pub fn unknown_events(mut self, unknown_events: UnknownEvents) -> Self {
+        self.unknown_events = unknown_events;
+        self
+    }
+
     pub fn header(mut self, name: String, value: String) -> Result<Self> {
         let name = HeaderName::from_str(name.as_str())?;
         let value = HeaderValue::from_str(value.as_str())?;
@@ -335,9 +779,25 @@ impl WebClientBuilder {
             builder = builder.add_default_header((key.clone(), value.clone()));
         }
 
+        let base_url = Rc::new(self.api_url.unwrap_or_else(rest_api_url));
+        let awc = builder.finish();
+        let limiters = Rc::new(ClientLimiters::new(&self.limits, &self.api_limits));
+        let breaker = self.circuit_breaker.map(|config| {
+            let probe = identity_probe(awc.clone(), base_url.clone());
+            Rc::new(Breaker::new(config, probe))
+        });
         WebClient {
-            base_url: Rc::new(self.api_url.unwrap_or_else(rest_api_url)),
-            awc: builder.finish(),
+            base_url,
+            awc,
+            max_body_size: self.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE),
+            timeout: self.timeout,
+            limits: limiters.chain(None),
+            limiters,
+            breaker,
+            cancel: None,
+            hooks: self.hooks,
+            unknown_events: self.unknown_events,
+            auth: None,
         }
     }
 }
@@ -349,10 +809,34 @@ impl Default for WebClientBuilder {
             auth: None,
             headers: HeaderMap::new(),
             timeout: None,
+            max_body_size: None,
+            limits: Limits::default(),
+            api_limits: HashMap::new(),
+            circuit_breaker: None,
+            hooks: Hooks::default(),
+            unknown_events: UnknownEvents::default(),
         }
     }
 }
 
+/// Probes the daemon with a request to the `me` endpoint of the Identity API.
+fn identity_probe(awc: awc::Client, base_url: Rc<Url>) -> breaker::Probe {
+    use crate::identity::IdentityApi;
+
+    let url = IdentityApi::rebase_service_url(base_url).and_then(|url| Ok(url.join("me")?));
+    Box::new(move |timeout| {
+        let request = match &url {
+            Ok(url) => awc.get(url.as_str()).timeout(timeout),
+            Err(e) => {
+                log::warn!("unable to probe the daemon: {}", e);
+                return futures::future::ready(false).boxed_local();
+            }
+        };
+        // any response means the daemon is alive
+        async move { request.send().await.is_ok() }.boxed_local()
+    })
+}
+
 /// Builder for the query part of the URLs.
 pub struct QueryParamsBuilder<'a> {
     serializer: form_urlencoded::Serializer<'a, String>,
@@ -404,12 +888,7 @@ impl TryFrom<String> for Event {
             match split[0] {
                 "event" => event = value.into(),
                 "data" => data.push(value.into()),
-                "id" => {
-                    id = match value.parse::<u64>() {
-                        Ok(id) => Some(id),
-                        _ => None,
-                    }
-                }
+                "id" => id = value.parse::<u64>().ok(),
                 _ => (),
             }
         }
@@ -521,6 +1000,174 @@ where
     }
 }
 
+/// Stream of elements incrementally deserialized from a JSON array body.
+pub struct JsonArrayStream<S, E, T>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+{
+    inner: S,
+    buffer: BytesMut,
+    scanner: JsonArrayScanner,
+    item: PhantomData<fn() -> T>,
+}
+
+enum JsonArrayStep {
+    Element(BytesMut),
+    End,
+    NeedMore,
+    Invalid(String),
+}
+
+#[derive(Default)]
+struct JsonArrayScanner {
+    started: bool,
+    closed: bool,
+    done: bool,
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonArrayScanner {
+    fn step(&mut self, buffer: &mut BytesMut) -> JsonArrayStep {
+        if self.done {
+            return JsonArrayStep::End;
+        }
+        while self.pos < buffer.len() {
+            let b = buffer[self.pos];
+            if !self.started {
+                if b.is_ascii_whitespace() {
+                    self.pos += 1;
+                    continue;
+                }
+                if b != b'[' {
+                    self.done = true;
+                    return JsonArrayStep::Invalid(format!(
+                        "expected `[`, but got: `{}`",
+                        char::from(b)
+                    ));
+                }
+                let _ = buffer.split_to(self.pos + 1);
+                self.pos = 0;
+                self.started = true;
+                continue;
+            }
+            if self.closed {
+                if b.is_ascii_whitespace() {
+                    self.pos += 1;
+                    continue;
+                }
+                self.done = true;
+                return JsonArrayStep::Invalid(format!(
+                    "trailing characters after JSON array: `{}`",
+                    char::from(b)
+                ));
+            }
+            if self.in_string {
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => (),
+                }
+                self.pos += 1;
+                continue;
+            }
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => self.depth -= 1,
+                b',' | b']' if self.depth == 0 => {
+                    let element = buffer.split_to(self.pos);
+                    let _ = buffer.split_to(1);
+                    self.pos = 0;
+                    let is_empty = element.iter().all(u8::is_ascii_whitespace);
+                    if b == b']' {
+                        // keep scanning, only whitespace may follow the array
+                        self.closed = true;
+                        if is_empty {
+                            continue;
+                        }
+                    } else if is_empty {
+                        self.done = true;
+                        return JsonArrayStep::Invalid("missing array element".into());
+                    }
+                    return JsonArrayStep::Element(element);
+                }
+                _ => (),
+            }
+            self.pos += 1;
+        }
+        JsonArrayStep::NeedMore
+    }
+
+    fn finish(&mut self, buffer: &BytesMut) -> JsonArrayStep {
+        let was_done = self.done;
+        self.done = true;
+        if was_done || self.closed || (!self.started && buffer.iter().all(u8::is_ascii_whitespace))
+        {
+            // empty body is treated as an empty array
+            JsonArrayStep::End
+        } else {
+            JsonArrayStep::Invalid("unexpected end of JSON array".into())
+        }
+    }
+}
+
+impl<S, E, T> JsonArrayStream<S, E, T>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Into<Error>,
+    T: DeserializeOwned,
+{
+    pub fn new(stream: S) -> Self {
+        JsonArrayStream {
+            inner: stream,
+            buffer: BytesMut::new(),
+            scanner: JsonArrayScanner::default(),
+            item: PhantomData,
+        }
+    }
+
+    fn map_step(step: JsonArrayStep) -> Poll<Option<Result<T>>> {
+        match step {
+            JsonArrayStep::Element(bytes) => {
+                Poll::Ready(Some(serde_json::from_slice(&bytes).map_err(Error::from)))
+            }
+            JsonArrayStep::Invalid(msg) => Poll::Ready(Some(Err(Error::from(
+                <serde_json::Error as serde::de::Error>::custom(msg),
+            )))),
+            JsonArrayStep::End => Poll::Ready(None),
+            JsonArrayStep::NeedMore => Poll::Pending,
+        }
+    }
+}
+
+impl<S, E, T> Stream for JsonArrayStream<S, E, T>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Into<Error>,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.scanner.step(&mut this.buffer) {
+                JsonArrayStep::NeedMore => (),
+                step => return Self::map_step(step),
+            }
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(bytes)) => this.buffer.extend_from_slice(&bytes),
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => return Self::map_step(this.scanner.finish(&this.buffer)),
+            }
+        }
+    }
+}
+
 /// Macro to facilitate URL formatting for REST API async bindings
 ///
 /// Supports query parameters, in addition to working similarly to format!(..).
@@ -562,7 +1209,7 @@ where
 #[rustfmt::skip]
 mod tests {
     use bytes::Bytes;
-    use crate::web::EventStream;
+    use crate::web::{EventStream, JsonArrayStream};
     use futures::{StreamExt, FutureExt, Stream};
     use crate::Error;
 
@@ -713,4 +1360,179 @@ id
             EventStream::new(stream)
         }).await.unwrap();
     }
+
+    fn chunked(s: &'static str, size: usize) -> impl Stream<Item = std::result::Result<Bytes, Error>> + Unpin {
+        futures::stream::iter(s.as_bytes()).chunks(size).map(|v| {
+            Ok::<_, Error>(Bytes::from(v.iter().map(|b| **b).collect::<Vec<_>>()))
+        })
+    }
+
+    #[actix_rt::test]
+    async fn json_array_stream() {
+        let src = r#" [ {"a": [1, 2], "b": "x,]}\"y"}, {"a": [], "b": ""} ,{"a":[3],"b":"[{"}] "#;
+        for size in [1, 3, 7, src.len()] {
+            let items = JsonArrayStream::<_, _, serde_json::Value>::new(chunked(src, size))
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(items.len(), 3);
+            let items = items.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(items[0], serde_json::json!({"a": [1, 2], "b": "x,]}\"y"}));
+            assert_eq!(items[1], serde_json::json!({"a": [], "b": ""}));
+            assert_eq!(items[2], serde_json::json!({"a": [3], "b": "[{"}));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn json_array_stream_empty() {
+        for src in ["[]", " [ ] ", ""] {
+            let items = JsonArrayStream::<_, _, u32>::new(chunked(src, 1))
+                .collect::<Vec<_>>()
+                .await;
+            assert!(items.is_empty());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn json_array_stream_invalid() {
+        let items = JsonArrayStream::<_, _, u32>::new(chunked("[1, 2", 2))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(*items[0].as_ref().unwrap(), 1);
+        assert!(items[1].is_err());
+
+        let items = JsonArrayStream::<_, _, u32>::new(chunked(r#"{"a": 1}"#, 2))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+
+        let items = JsonArrayStream::<_, _, u32>::new(chunked(r#"[1, "x", 3]"#, 4))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items.len(), 3);
+        assert!(items[1].is_err());
+        assert_eq!(*items[2].as_ref().unwrap(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn json_array_stream_trailing_bytes() {
+        for size in [1, 4, 32] {
+            let items = JsonArrayStream::<_, _, u32>::new(chunked("[1, 2] \n", size))
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(items.into_iter().collect::<Result<Vec<_>, _>>().unwrap(), [1, 2]);
+
+            let items = JsonArrayStream::<_, _, u32>::new(chunked("[1, 2] [3]", size))
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(items.len(), 3);
+            assert_eq!(*items[1].as_ref().unwrap(), 2);
+            assert!(matches!(items[2], Err(Error::JsonError(_))));
+
+            let items = JsonArrayStream::<_, _, u32>::new(chunked("[]x", size))
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(items.len(), 1);
+            assert!(items[0].is_err());
+        }
+    }
+
+    #[test]
+    fn long_poll_timeout() {
+        use super::{WebClient, LONG_POLL_TIMEOUT_MARGIN};
+        use std::time::Duration;
+
+        let client = WebClient::builder()
+            .timeout(Duration::from_secs(10))
+            .build();
+        let timeout = |server_timeout: Option<u64>| {
+            client
+                .get("events")
+                .long_poll(server_timeout.map(Duration::from_secs))
+                .timeout
+        };
+        assert_eq!(timeout(None), Some(Duration::from_secs(10)));
+        assert_eq!(timeout(Some(1)), Some(Duration::from_secs(10)));
+        assert_eq!(
+            timeout(Some(30)),
+            Some(Duration::from_secs(30) + LONG_POLL_TIMEOUT_MARGIN)
+        );
+
+        let client = WebClient::builder().build();
+        let request = client.get("events").long_poll(Some(Duration::from_secs(30)));
+        assert_eq!(request.timeout, None);
+    }
+
+    #[test]
+    fn long_poll_default_timeout() {
+        use super::{WebClient, DEFAULT_LONG_POLL_TIMEOUT, LONG_POLL_TIMEOUT_MARGIN};
+        use std::time::Duration;
+
+        let client = WebClient::builder()
+            .timeout(Duration::from_secs(3))
+            .build();
+        let request = client.get("events").long_poll(None);
+        assert_eq!(
+            request.timeout,
+            Some(DEFAULT_LONG_POLL_TIMEOUT + LONG_POLL_TIMEOUT_MARGIN)
+        );
+        let request = client
+            .get("events")
+            .long_poll(Some(Duration::from_secs(1)));
+        assert_eq!(request.timeout, Some(Duration::from_secs(6)));
+    }
+
+    #[actix_rt::test]
+    async fn cancel_in_flight_request() {
+        use super::WebClient;
+        use std::time::{Duration, Instant};
+        use tokio_util::sync::CancellationToken;
+
+        // accepts connections but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let cancel = CancellationToken::new();
+        let client = WebClient::builder()
+            .api_url(url.parse().unwrap())
+            .build()
+            .with_cancellation(cancel.clone());
+
+        actix_rt::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+        let started = Instant::now();
+        let result = client.get("events").send().json::<Vec<u32>>().await;
+        assert!(matches!(result, Err(Error::Cancelled { .. })));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[actix_rt::test]
+    async fn limits_share_request_timeout() {
+        use super::{Limits, WebClient};
+        use std::time::{Duration, Instant};
+
+        // accepts connections but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = WebClient::builder()
+            .api_url(url.parse().unwrap())
+            .timeout(Duration::from_millis(400))
+            .limits(Limits::default().rate_limit(4.0, 1).unwrap())
+            .build();
+
+        let started = Instant::now();
+        let request = |url| {
+            let request = client.get(url).send();
+            async move {
+                let result = request.json::<Vec<u32>>().await;
+                assert!(matches!(result, Err(Error::TimeoutError { .. })));
+                started.elapsed()
+            }
+        };
+        // the second request waits 250 ms for a token, then 150 ms for a response
+        let (_, elapsed) = futures::join!(request("first"), request("second"));
+        assert!(elapsed < Duration::from_millis(550), "{:?}", elapsed);
+    }
 }
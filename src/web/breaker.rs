@@ -0,0 +1,278 @@
+//! Circuit breaker failing requests fast while the daemon is unresponsive
+use futures::future::LocalBoxFuture;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// State of the [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail immediately.
+    Open,
+    /// The daemon is being probed; requests fail immediately until the probe succeeds.
+    HalfOpen,
+}
+
+/// Configuration of the circuit breaker of a [`WebClient`](super::WebClient).
+///
+/// The breaker opens after `failure_threshold` consecutive requests failed
+/// to reach the daemon (see [`Error::SendRequestError`](crate::Error::SendRequestError)
+/// and [`Error::TimeoutError`](crate::Error::TimeoutError)).
+/// While open, requests fail with [`Error::CircuitOpen`](crate::Error::CircuitOpen).
+/// After `reset_timeout` the next request probes the daemon with
+/// [`IdentityApi::me`](crate::identity::IdentityApi::me) and the breaker closes
+/// if the daemon responds.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    probe_timeout: Duration,
+    on_state_change: Option<Rc<dyn Fn(CircuitState, CircuitState)>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+            on_state_change: None,
+        }
+    }
+
+    /// Sets the timeout of the probe request.
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Sets a callback invoked with the previous and the new state on every state change.
+    pub fn on_state_change(mut self, f: impl Fn(CircuitState, CircuitState) + 'static) -> Self {
+        self.on_state_change = Some(Rc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("failure_threshold", &self.failure_threshold)
+            .field("reset_timeout", &self.reset_timeout)
+            .field("probe_timeout", &self.probe_timeout)
+            .finish()
+    }
+}
+
+/// Checks whether the daemon responds within given timeout.
+pub(crate) type Probe = Box<dyn Fn(Duration) -> LocalBoxFuture<'static, bool>>;
+
+struct BreakerState {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+}
+
+/// Circuit breaker shared by all clients created from the same builder.
+pub(crate) struct Breaker {
+    config: CircuitBreaker,
+    probe: Probe,
+    state: RefCell<BreakerState>,
+}
+
+impl Breaker {
+    pub(crate) fn new(config: CircuitBreaker, probe: Probe) -> Self {
+        Breaker {
+            config,
+            probe,
+            state: RefCell::new(BreakerState {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub(crate) fn state(&self) -> CircuitState {
+        self.state.borrow().state
+    }
+
+    fn transition(&self, to: CircuitState) {
+        let from = {
+            let mut state = self.state.borrow_mut();
+            let from = state.state;
+            state.state = to;
+            match to {
+                CircuitState::Open => state.opened_at = Instant::now(),
+                CircuitState::Closed => state.failures = 0,
+                CircuitState::HalfOpen => (),
+            }
+            from
+        };
+        if from != to {
+            log::debug!("circuit breaker state change: {:?} -> {:?}", from, to);
+            if let Some(f) = &self.config.on_state_change {
+                f(from, to);
+            }
+        }
+    }
+
+    /// Checks whether a request may be sent, probing the daemon if it is time to.
+    pub(crate) async fn admit(&self) -> bool {
+        let should_probe = {
+            let state = self.state.borrow();
+            match state.state {
+                CircuitState::Closed => return true,
+                CircuitState::HalfOpen => return false,
+                CircuitState::Open => state.opened_at.elapsed() >= self.config.reset_timeout,
+            }
+        };
+        if !should_probe {
+            return false;
+        }
+
+        self.transition(CircuitState::HalfOpen);
+        let mut guard = ProbeGuard {
+            breaker: self,
+            finished: false,
+        };
+        let alive = (self.probe)(self.config.probe_timeout).await;
+        guard.finished = true;
+        if alive {
+            self.transition(CircuitState::Closed);
+        } else {
+            self.transition(CircuitState::Open);
+        }
+        alive
+    }
+
+    pub(crate) fn record_success(&self) {
+        self.state.borrow_mut().failures = 0;
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let trip = {
+            let mut state = self.state.borrow_mut();
+            state.failures += 1;
+            state.state == CircuitState::Closed && state.failures >= self.config.failure_threshold
+        };
+        if trip {
+            self.transition(CircuitState::Open);
+        }
+    }
+}
+
+/// Opens the breaker again if the probe is dropped before it finishes,
+/// eg. when the request probing the daemon times out or is cancelled.
+struct ProbeGuard<'a> {
+    breaker: &'a Breaker,
+    finished: bool,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.breaker.transition(CircuitState::Open);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use std::cell::Cell;
+
+    fn breaker(probe_ok: Rc<Cell<bool>>, changes: Rc<RefCell<Vec<CircuitState>>>) -> Breaker {
+        let config = CircuitBreaker::new(2, Duration::from_millis(20))
+            .on_state_change(move |_, to| changes.borrow_mut().push(to));
+        Breaker::new(
+            config,
+            Box::new(move |_| {
+                let ok = probe_ok.get();
+                async move { ok }.boxed_local()
+            }),
+        )
+    }
+
+    #[actix_rt::test]
+    async fn trips_after_consecutive_failures() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let breaker = breaker(Rc::new(Cell::new(true)), changes.clone());
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.admit().await);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.admit().await);
+        assert_eq!(*changes.borrow(), vec![CircuitState::Open]);
+    }
+
+    #[actix_rt::test]
+    async fn probes_after_reset_timeout() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let probe_ok = Rc::new(Cell::new(false));
+        let breaker = breaker(probe_ok.clone(), changes.clone());
+
+        breaker.record_failure();
+        breaker.record_failure();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        assert!(!breaker.admit().await);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.admit().await);
+
+        probe_ok.set(true);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.admit().await);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        use CircuitState::*;
+        assert_eq!(
+            *changes.borrow(),
+            vec![Open, HalfOpen, Open, HalfOpen, Closed]
+        );
+
+        // failures are counted from zero again
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[actix_rt::test]
+    async fn dropped_probe_reopens() {
+        let hang = Rc::new(Cell::new(true));
+        let probe_hang = hang.clone();
+        let breaker = Breaker::new(
+            CircuitBreaker::new(1, Duration::from_millis(20)),
+            Box::new(move |_| {
+                if probe_hang.get() {
+                    futures::future::pending().boxed_local()
+                } else {
+                    async { true }.boxed_local()
+                }
+            }),
+        );
+
+        breaker.record_failure();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let probe = tokio::time::timeout(Duration::from_millis(10), breaker.admit()).await;
+        assert!(probe.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        // the reset timeout starts anew
+        assert!(!breaker.admit().await);
+
+        hang.set(false);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.admit().await);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
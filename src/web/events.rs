@@ -0,0 +1,145 @@
+//! Handling of events of types unknown to this version of the client
+use crate::model::activity::provider_event::{self as activity, ProviderEventType};
+use crate::model::activity::{RuntimeEvent, RuntimeEventKind};
+use crate::model::market::{
+    AgreementEventType, AgreementOperationEvent, ProviderEvent, RequestorEvent,
+};
+use crate::model::payment::{DebitNoteEvent, DebitNoteEventType, InvoiceEvent, InvoiceEventType};
+
+/// What methods collecting events, eg. [`MarketRequestorApi::collect`](
+/// crate::market::MarketRequestorApi::collect) or [`EventsBuilder::get`](
+/// crate::payment::api::EventsBuilder::get), do with events of types introduced
+/// in newer versions of the API.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownEvents {
+    /// Events are logged and skipped.
+    #[default]
+    Skip,
+    /// Events are returned as the `Unknown` variant of the event enum.
+    Surface,
+}
+
+impl UnknownEvents {
+    pub(crate) fn apply<E: ApiEvent>(self, url: &str, mut events: Vec<E>) -> Vec<E> {
+        events.retain(|event| self.keep(url, event));
+        events
+    }
+
+    pub(crate) fn keep<E: ApiEvent>(self, url: &str, event: &E) -> bool {
+        match event.unknown_event_type() {
+            Some(event_type) if self == UnknownEvents::Skip => {
+                log::warn!("skipping event of unknown type {} from {}", event_type, url);
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Event which may be of a type unknown to this version of the client.
+pub trait ApiEvent {
+    /// Type of the event, if it is unknown.
+    fn unknown_event_type(&self) -> Option<&str>;
+}
+
+impl ApiEvent for RequestorEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match self {
+            RequestorEvent::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for ProviderEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match self {
+            ProviderEvent::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for activity::ProviderEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match &self.event_type {
+            ProviderEventType::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for RuntimeEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match &self.kind {
+            RuntimeEventKind::Unknown { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for AgreementOperationEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match &self.event_type {
+            AgreementEventType::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for DebitNoteEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match &self.event_type {
+            DebitNoteEventType::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+impl ApiEvent for InvoiceEvent {
+    fn unknown_event_type(&self) -> Option<&str> {
+        match &self.event_type {
+            InvoiceEventType::Unknown { event_type, .. } => Some(event_type),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn events() -> Vec<DebitNoteEvent> {
+        serde_json::from_value(json!([
+            {
+                "debitNoteId": "dn1",
+                "eventDate": "2020-12-21T15:51:21.126645Z",
+                "eventType": "DebitNoteAcceptedEvent",
+            },
+            {
+                "debitNoteId": "dn2",
+                "eventDate": "2020-12-21T15:51:22.126645Z",
+                "eventType": "DebitNoteExpiredEvent",
+            },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn skip_unknown_events() {
+        let events = UnknownEvents::Skip.apply("debitNoteEvents", events());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].debit_note_id, "dn1");
+    }
+
+    #[test]
+    fn surface_unknown_events() {
+        let events = UnknownEvents::Surface.apply("debitNoteEvents", events());
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[1].unknown_event_type(),
+            Some("DebitNoteExpiredEvent")
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Hooks for inspecting and altering requests sent by a `WebClient` and their responses
+use awc::http::header::HeaderMap;
+use awc::http::{Method, StatusCode};
+use awc::ws::{Frame, Message};
+use bytes::Bytes;
+use std::fmt;
+use std::rc::Rc;
+
+use super::Event;
+use crate::{Error, Result};
+
+/// Outgoing request passed to [`Hook::on_request`].
+pub struct Request<'a> {
+    pub method: &'a Method,
+    pub url: &'a str,
+    pub headers: &'a mut HeaderMap,
+    /// Body of requests sent with [`send_json`](super::WebRequest::send_json) or
+    /// [`send_bytes`](super::WebRequest::send_bytes), `None` for other requests,
+    /// event streams and WebSocket connections.
+    pub body: Option<&'a mut Vec<u8>>,
+}
+
+/// Head of an incoming response passed to [`Hook::on_response`]
+/// and [`Hook::on_response_body`].
+pub struct Response<'a> {
+    pub method: &'a Method,
+    pub url: &'a str,
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+}
+
+/// Hook invoked with every request sent by a [`WebClient`](super::WebClient) and its
+/// response, including event streams and WebSocket connections.
+///
+/// Hooks may inspect and alter request and response bodies, events and WebSocket
+/// messages, or fail them, eg. to add correlation IDs, redact logs, collect audit
+/// records or inject faults.
+///
+/// Hooks are registered with [`WebClientBuilder::hook`](super::WebClientBuilder::hook).
+/// Outgoing requests and messages pass through them in the order of registration,
+/// incoming responses, events and frames in the reverse order.
+pub trait Hook {
+    /// Inspects or alters the request headers and body before the request is sent.
+    ///
+    /// Returning an error fails the request without sending it.
+    fn on_request(&self, _request: &mut Request<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects the response status and headers, before the status is checked.
+    ///
+    /// Returning an error fails the request with it.
+    fn on_response(&self, _response: &Response<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects or alters the body of a successful response before it is deserialized.
+    ///
+    /// Bodies read into memory, eg. by [`json`](super::WebRequest::json), are passed
+    /// whole, and streamed ones, eg. by [`json_stream`](super::WebRequest::json_stream),
+    /// chunk by chunk. Returning an error fails the request with it.
+    fn on_response_body(&self, _response: &Response<'_>, _body: &mut Bytes) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects or alters an event received from an event stream.
+    ///
+    /// Returning an error yields it from the stream instead of the event.
+    fn on_event(&self, _url: &str, _event: &mut Event) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects or alters a message before it is sent over a WebSocket connection.
+    ///
+    /// Returning an error fails sending the message.
+    fn on_ws_message(&self, _url: &str, _message: &mut Message) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects or alters a frame received over a WebSocket connection.
+    ///
+    /// Returning an error yields it from the connection instead of the frame.
+    fn on_ws_frame(&self, _url: &str, _frame: &mut Frame) -> Result<()> {
+        Ok(())
+    }
+
+    /// Inspects the error of a request which failed without a response.
+    fn on_error(&self, _method: &Method, _url: &str, _error: &Error) {}
+}
+
+/// Hooks shared by all clients created from the same builder.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks(Vec<Rc<dyn Hook>>);
+
+impl Hooks {
+    pub(crate) fn push(&mut self, hook: Rc<dyn Hook>) {
+        self.0.push(hook);
+    }
+
+    pub(crate) fn on_request(
+        &self,
+        method: &Method,
+        url: &str,
+        headers: &mut HeaderMap,
+        body: Option<&mut Vec<u8>>,
+    ) -> Result<()> {
+        let mut request = Request {
+            method,
+            url,
+            headers,
+            body,
+        };
+        self.0
+            .iter()
+            .try_for_each(|hook| hook.on_request(&mut request))
+    }
+
+    pub(crate) fn on_response(
+        &self,
+        method: &Method,
+        url: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Result<()> {
+        let response = Response {
+            method,
+            url,
+            status,
+            headers,
+        };
+        self.0
+            .iter()
+            .rev()
+            .try_for_each(|hook| hook.on_response(&response))
+    }
+
+    pub(crate) fn on_response_body(
+        &self,
+        method: &Method,
+        url: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        mut body: Bytes,
+    ) -> Result<Bytes> {
+        let response = Response {
+            method,
+            url,
+            status,
+            headers,
+        };
+        for hook in self.0.iter().rev() {
+            hook.on_response_body(&response, &mut body)?;
+        }
+        Ok(body)
+    }
+
+    pub(crate) fn on_event(&self, url: &str, mut event: Event) -> Result<Event> {
+        for hook in self.0.iter().rev() {
+            hook.on_event(url, &mut event)?;
+        }
+        Ok(event)
+    }
+
+    pub(crate) fn on_ws_message(&self, url: &str, message: &mut Message) -> Result<()> {
+        self.0
+            .iter()
+            .try_for_each(|hook| hook.on_ws_message(url, message))
+    }
+
+    pub(crate) fn on_ws_frame(&self, url: &str, frame: &mut Frame) -> Result<()> {
+        self.0
+            .iter()
+            .rev()
+            .try_for_each(|hook| hook.on_ws_frame(url, frame))
+    }
+
+    pub(crate) fn on_error(&self, method: &Method, url: &str, error: &Error) {
+        for hook in self.0.iter().rev() {
+            hook.on_error(method, url, error);
+        }
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hooks({} hooks)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::web::WebClient;
+    use futures::{SinkExt, StreamExt, TryStreamExt};
+    use std::cell::RefCell;
+    use std::thread::JoinHandle;
+
+    /// Responds to a single request with an empty JSON array.
+    fn serve_once() -> (String, JoinHandle<Vec<testing::Request>>) {
+        testing::serve(1, |_| testing::Response::json("[]"))
+    }
+
+    fn client(url: &str, hook: impl Hook + 'static) -> WebClient {
+        WebClient::builder()
+            .api_url(url.parse().unwrap())
+            .hook(hook)
+            .build()
+    }
+
+    #[derive(Default)]
+    struct Audit {
+        correlation_id: &'static str,
+        records: Rc<RefCell<Vec<(Method, StatusCode)>>>,
+        reject_response: bool,
+    }
+
+    impl Hook for Audit {
+        fn on_request(&self, request: &mut Request<'_>) -> Result<()> {
+            request.headers.insert(
+                "x-correlation-id".parse().unwrap(),
+                self.correlation_id.parse().unwrap(),
+            );
+            Ok(())
+        }
+
+        fn on_response(&self, response: &Response<'_>) -> Result<()> {
+            self.records
+                .borrow_mut()
+                .push((response.method.clone(), response.status));
+            if self.reject_response {
+                return Err(Error::InternalError("rejected".into()));
+            }
+            Ok(())
+        }
+    }
+
+    struct Fault;
+
+    impl Hook for Fault {
+        fn on_request(&self, _request: &mut Request<'_>) -> Result<()> {
+            Err(Error::InternalError("injected".into()))
+        }
+    }
+
+    /// Masks secrets in outgoing bodies and upper-cases incoming ones.
+    struct Rewrite;
+
+    impl Hook for Rewrite {
+        fn on_request(&self, request: &mut Request<'_>) -> Result<()> {
+            if let Some(body) = request.body.as_deref_mut() {
+                *body = String::from_utf8_lossy(body)
+                    .replace("secret", "***")
+                    .into_bytes();
+            }
+            Ok(())
+        }
+
+        fn on_response_body(&self, _response: &Response<'_>, body: &mut Bytes) -> Result<()> {
+            *body = body.to_ascii_uppercase().into();
+            Ok(())
+        }
+
+        fn on_event(&self, _url: &str, event: &mut Event) -> Result<()> {
+            if event.event == "fail" {
+                return Err(Error::EventStreamError("rejected".into()));
+            }
+            event.data = event.data.to_uppercase();
+            Ok(())
+        }
+
+        fn on_ws_message(&self, _url: &str, message: &mut Message) -> Result<()> {
+            if let Message::Binary(data) = message {
+                *data = [data.as_ref(), b"!"].concat().into();
+            }
+            Ok(())
+        }
+
+        fn on_ws_frame(&self, _url: &str, frame: &mut Frame) -> Result<()> {
+            if let Frame::Binary(data) = frame {
+                *data = data.to_ascii_uppercase().into();
+            }
+            Ok(())
+        }
+    }
+
+    #[actix_rt::test]
+    async fn alters_request_and_inspects_response() {
+        let (url, server) = serve_once();
+        let records = Rc::new(RefCell::new(Vec::new()));
+        let client = client(
+            &url,
+            Audit {
+                correlation_id: "abc-123",
+                records: records.clone(),
+                ..Default::default()
+            },
+        );
+
+        let items: Vec<u32> = client.get("items").send().json().await.unwrap();
+        assert!(items.is_empty());
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].header("x-correlation-id"), Some("abc-123"));
+        assert_eq!(*records.borrow(), vec![(Method::GET, StatusCode::OK)]);
+    }
+
+    #[actix_rt::test]
+    async fn fails_rejected_response() {
+        let (url, server) = serve_once();
+        let client = client(
+            &url,
+            Audit {
+                reject_response: true,
+                ..Default::default()
+            },
+        );
+
+        let result = client.get("items").send().json::<Vec<u32>>().await;
+        assert!(matches!(result, Err(Error::InternalError(msg)) if msg == "rejected"));
+        server.join().unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn fault_injection() {
+        let records = Rc::new(RefCell::new(Vec::new()));
+        let client = WebClient::builder()
+            .api_url("http://127.0.0.1:1".parse().unwrap())
+            .hook(Fault)
+            .hook(Audit {
+                records: records.clone(),
+                ..Default::default()
+            })
+            .build();
+
+        let result = client.get("items").send().json::<Vec<u32>>().await;
+        assert!(matches!(result, Err(Error::InternalError(msg)) if msg == "injected"));
+        assert!(client.event_stream("events").await.is_err());
+        assert!(client.ws("ws").await.is_err());
+        assert!(records.borrow().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn alters_json_bodies() {
+        let (url, server) =
+            testing::serve(2, |request| testing::Response::json(request.body.clone()));
+        let client = client(&url, Rewrite);
+
+        let echo: serde_json::Value = client
+            .post("items")
+            .send_json(&serde_json::json!({"token": "secret"}))
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(echo, serde_json::json!({"TOKEN": "***"}));
+        let echo = client.post("items").send_bytes(b"secret".to_vec()).bytes();
+        assert_eq!(echo.await.unwrap(), b"***");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].body, r#"{"token":"***"}"#);
+        assert_eq!(requests[0].header("content-type"), Some("application/json"));
+        assert_eq!(requests[1].body, "***");
+    }
+
+    #[actix_rt::test]
+    async fn alters_events() {
+        let (url, server) = testing::serve(1, |_| {
+            testing::Response::events("event: a\ndata: x\n\nevent: fail\ndata: y\n\n")
+        });
+        let client = client(&url, Rewrite);
+
+        let events: Vec<_> = client.event_stream("events").await.unwrap().collect().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].as_ref().unwrap().data, "X");
+        assert!(matches!(&events[1], Err(Error::EventStreamError(msg)) if msg == "rejected"));
+        server.join().unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn alters_ws_messages() {
+        let server = testing::ws_echo_server().await;
+        let client = client(&format!("http://{server}/"), Rewrite);
+
+        let (_, mut conn) = client.ws("ws").await.unwrap();
+        conn.send(Message::Binary("ping".into())).await.unwrap();
+        let frame = conn.try_next().await.unwrap();
+        assert!(matches!(frame, Some(Frame::Binary(data)) if data == "PING!"));
+    }
+}
@@ -0,0 +1,305 @@
+//! Client-side request rate limiting and concurrency caps
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{cell::RefCell, collections::HashMap};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::{Error, Result};
+
+/// Limits applied to requests sent by a [`WebClient`](super::WebClient).
+///
+/// Requests exceeding the limits are queued until they can be sent
+/// or until the client timeout elapses.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    rate: Option<(f64, u32)>,
+    max_in_flight: Option<usize>,
+}
+
+impl Limits {
+    /// Allows at most `requests_per_second` requests on average,
+    /// with bursts of up to `burst` requests.
+    ///
+    /// Fails unless `requests_per_second` is a positive number.
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Result<Self> {
+        if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+            return Err(Error::InvalidRateLimit(requests_per_second));
+        }
+        self.rate = Some((requests_per_second, burst.max(1)));
+        Ok(self)
+    }
+
+    /// Allows at most `max_in_flight` requests to be processed at the same time.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight.max(1));
+        self
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: u32, now: Instant) -> Self {
+        let capacity = burst as f64;
+        TokenBucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: now,
+        }
+    }
+
+    /// Reserves a token and returns how long to wait before it may be used.
+    ///
+    /// Tokens are handed out in the order of reservations,
+    /// so the balance goes below zero when requests are queued.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::try_from_secs_f64(-self.tokens / self.rate).unwrap_or(Duration::MAX)
+        }
+    }
+
+    /// Returns a reserved token which has not been used.
+    fn refund(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.capacity);
+    }
+}
+
+/// Token reserved in a bucket, returned to it unless the request is let through,
+/// eg. when waiting for the token is cancelled by a timeout.
+struct Reservation<'a> {
+    bucket: &'a RefCell<TokenBucket>,
+    used: bool,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.used {
+            self.bucket.borrow_mut().refund();
+        }
+    }
+}
+
+struct Limiter {
+    bucket: Option<RefCell<TokenBucket>>,
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+impl Limiter {
+    fn new(limits: &Limits) -> Option<Rc<Self>> {
+        if limits == &Limits::default() {
+            return None;
+        }
+        Some(Rc::new(Limiter {
+            bucket: limits
+                .rate
+                .map(|(rate, burst)| RefCell::new(TokenBucket::new(rate, burst, Instant::now()))),
+            semaphore: limits.max_in_flight.map(|n| Arc::new(Semaphore::new(n))),
+        }))
+    }
+}
+
+/// Limiters shared by all clients created from the same builder.
+#[derive(Default)]
+pub(crate) struct ClientLimiters {
+    global: Option<Rc<Limiter>>,
+    per_api: HashMap<&'static str, Rc<Limiter>>,
+}
+
+impl ClientLimiters {
+    pub(crate) fn new(global: &Limits, per_api: &HashMap<&'static str, Limits>) -> Self {
+        ClientLimiters {
+            global: Limiter::new(global),
+            per_api: per_api
+                .iter()
+                .filter_map(|(api, limits)| Limiter::new(limits).map(|l| (*api, l)))
+                .collect(),
+        }
+    }
+
+    /// Returns limiters applicable to requests of the given API.
+    pub(crate) fn chain(&self, api: Option<&str>) -> LimiterChain {
+        let api = api.and_then(|api| self.per_api.get(api));
+        LimiterChain(self.global.iter().chain(api).cloned().collect())
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct LimiterChain(Vec<Rc<Limiter>>);
+
+/// Keeps the request counted as in-flight until dropped.
+pub(crate) struct LimitGuard {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl LimiterChain {
+    async fn acquire_all(&self) -> LimitGuard {
+        let mut permits = Vec::new();
+        for limiter in &self.0 {
+            if let Some(semaphore) = &limiter.semaphore {
+                // semaphores are never closed
+                if let Ok(permit) = semaphore.clone().acquire_owned().await {
+                    permits.push(permit);
+                }
+            }
+        }
+        let mut reservations = Vec::new();
+        for limiter in &self.0 {
+            if let Some(bucket) = &limiter.bucket {
+                let wait = bucket.borrow_mut().reserve(Instant::now());
+                reservations.push(Reservation {
+                    bucket,
+                    used: false,
+                });
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+        for reservation in &mut reservations {
+            reservation.used = true;
+        }
+        LimitGuard { _permits: permits }
+    }
+
+    /// Waits until the request may be sent.
+    ///
+    /// Returns `None` if the limits do not allow it within `timeout`,
+    /// in which case the tokens reserved for the request are returned.
+    pub(crate) async fn acquire(&self, timeout: Option<Duration>) -> Option<LimitGuard> {
+        if self.0.is_empty() {
+            return Some(LimitGuard {
+                _permits: Vec::new(),
+            });
+        }
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.acquire_all()).await.ok(),
+            None => Some(self.acquire_all().await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::join_all;
+    use std::cell::Cell;
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 2, now);
+
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(now), Duration::from_millis(1000));
+
+        let now = now + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::from_millis(500));
+    }
+
+    #[actix_rt::test]
+    async fn max_in_flight() {
+        let limiters = ClientLimiters::new(
+            &Limits::default().max_in_flight(4),
+            &[("api", Limits::default().max_in_flight(2))]
+                .into_iter()
+                .collect(),
+        );
+        let in_flight = Cell::new(0);
+        let max_seen = Cell::new(0);
+
+        let run = |api| {
+            let chain = limiters.chain(api);
+            let (in_flight, max_seen) = (&in_flight, &max_seen);
+            async move {
+                let _guard = chain.acquire(None).await.unwrap();
+                in_flight.set(in_flight.get() + 1);
+                max_seen.set(max_seen.get().max(in_flight.get()));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.set(in_flight.get() - 1);
+            }
+        };
+
+        join_all((0..10).map(|_| run(Some("api")))).await;
+        assert_eq!(max_seen.get(), 2);
+
+        max_seen.set(0);
+        join_all((0..10).map(|i| run((i % 2 == 0).then_some("other")))).await;
+        assert_eq!(max_seen.get(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn queued_request_times_out() {
+        let limiters = ClientLimiters::new(
+            &Limits::default()
+                .max_in_flight(1)
+                .rate_limit(1.0, 1)
+                .unwrap(),
+            &HashMap::new(),
+        );
+        let chain = limiters.chain(None);
+
+        let guard = chain.acquire(Some(Duration::from_millis(10))).await;
+        assert!(guard.is_some());
+        // no in-flight slot left
+        assert!(chain
+            .acquire(Some(Duration::from_millis(10)))
+            .await
+            .is_none());
+        drop(guard);
+        // no token left
+        assert!(chain
+            .acquire(Some(Duration::from_millis(10)))
+            .await
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn timed_out_requests_return_tokens() {
+        let limiters = ClientLimiters::new(
+            &Limits::default().rate_limit(1.0, 1).unwrap(),
+            &HashMap::new(),
+        );
+        let chain = limiters.chain(None);
+        assert!(chain.acquire(None).await.is_some());
+        for _ in 0..10 {
+            assert!(chain
+                .acquire(Some(Duration::from_millis(1)))
+                .await
+                .is_none());
+        }
+
+        let bucket = chain.0[0].bucket.as_ref().unwrap();
+        assert!(bucket.borrow_mut().reserve(Instant::now()) <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn invalid_rate() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                Limits::default().rate_limit(rate, 1),
+                Err(Error::InvalidRateLimit(_))
+            ));
+        }
+        let mut bucket = TokenBucket::new(f64::MIN_POSITIVE, 1, Instant::now());
+        bucket.reserve(Instant::now());
+        assert_eq!(bucket.reserve(Instant::now()), Duration::MAX);
+    }
+}
@@ -0,0 +1,85 @@
+//! WebSocket connection passing messages and frames through the client hooks
+use actix_codec::Framed;
+use awc::error::WsProtocolError;
+use awc::ws::{Codec, Frame, Message};
+use awc::BoxedSocket;
+use futures::{Sink, Stream};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use super::hooks::Hooks;
+use crate::Error;
+
+/// WebSocket connection opened with [`WebClient::ws`](super::WebClient::ws).
+///
+/// Sent messages pass through [`Hook::on_ws_message`](super::Hook::on_ws_message)
+/// and received frames through [`Hook::on_ws_frame`](super::Hook::on_ws_frame).
+/// Errors returned by the hooks are reported as [`WsProtocolError::Io`].
+pub struct WebSocket {
+    framed: Framed<BoxedSocket, Codec>,
+    hooks: Hooks,
+    url: String,
+}
+
+impl WebSocket {
+    pub(crate) fn new(framed: Framed<BoxedSocket, Codec>, hooks: Hooks, url: String) -> Self {
+        WebSocket { framed, hooks, url }
+    }
+
+    /// Underlying connection, which bypasses the hooks.
+    pub fn into_inner(self) -> Framed<BoxedSocket, Codec> {
+        self.framed
+    }
+}
+
+impl From<Framed<BoxedSocket, Codec>> for WebSocket {
+    /// Wraps a connection without any hooks.
+    fn from(framed: Framed<BoxedSocket, Codec>) -> Self {
+        WebSocket::new(framed, Hooks::default(), String::new())
+    }
+}
+
+fn hook_error(e: Error) -> WsProtocolError {
+    WsProtocolError::Io(io::Error::new(io::ErrorKind::Other, e.to_string()))
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Frame, WsProtocolError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let frame = ready!(Pin::new(&mut this.framed).poll_next(cx));
+        Poll::Ready(frame.map(|frame| {
+            let mut frame = frame?;
+            this.hooks
+                .on_ws_frame(&this.url, &mut frame)
+                .map_err(hook_error)?;
+            Ok(frame)
+        }))
+    }
+}
+
+impl Sink<Message> for WebSocket {
+    type Error = WsProtocolError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().framed).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, mut message: Message) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        this.hooks
+            .on_ws_message(&this.url, &mut message)
+            .map_err(hook_error)?;
+        Pin::new(&mut this.framed).start_send(message)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().framed).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().framed).poll_close(cx)
+    }
+}
@@ -0,0 +1,624 @@
+//! Conformance of the client and its model types with the OpenAPI specs in `specs/`.
+//!
+//! For every schema mapped to a `ya-client-model` type an example payload is generated,
+//! deserialized into the type and serialized back. Fields lost on the way, fields with
+//! different casing and type mismatches are reported, as well as spec endpoints
+//! which are not called anywhere in the client sources.
+//!
+//! Issues listed in `spec_conformance/known_issues.txt` are tolerated, so that the test
+//! fails only on regressions. Run with `SPEC_CONFORMANCE_BLESS=1` and `--all-features`
+//! to rewrite the list after fixing (or knowingly introducing) discrepancies.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ya_client::web::ApiEvent;
+use ya_client_model::{activity, market, net, payment};
+use ya_client_model::{identity::Identity, version, ErrorMessage};
+
+const SPEC_FILES: &[&str] = &[
+    "activity-api.yaml",
+    "common.yaml",
+    "gsb-api.yaml",
+    "identity-api.yaml",
+    "market-api.yaml",
+    "net-api-v2.yaml",
+    "net-api.yaml",
+    "payment-api.yaml",
+    "version-api.yaml",
+];
+
+/// Client sources implementing each API.
+const API_SOURCES: &[(&str, &[&str])] = &[
+    ("activity-api.yaml", &["src/activity.rs", "src/activity"]),
+    ("gsb-api.yaml", &[]),
+    ("identity-api.yaml", &["src/identity.rs"]),
+    ("market-api.yaml", &["src/market.rs", "src/market"]),
+    ("net-api-v2.yaml", &["src/net.rs"]),
+    ("net-api.yaml", &["src/net.rs"]),
+    ("payment-api.yaml", &["src/payment.rs", "src/payment"]),
+    ("version-api.yaml", &["src/version.rs"]),
+];
+
+const KNOWN_ISSUES: &str = "tests/spec_conformance/known_issues.txt";
+const MAX_DEPTH: usize = 12;
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+struct Specs(HashMap<&'static str, Value>);
+
+impl Specs {
+    fn load() -> Self {
+        Specs(
+            SPEC_FILES
+                .iter()
+                .map(|&file| {
+                    let path = root().join("specs").join(file);
+                    let yaml = fs::read_to_string(&path)
+                        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+                    let spec = serde_yaml::from_str(&yaml)
+                        .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+                    (file, spec)
+                })
+                .collect(),
+        )
+    }
+
+    fn schema(&self, file: &str, name: &str) -> &Value {
+        match self.0.get(file) {
+            Some(spec) => &spec["components"]["schemas"][name],
+            None => &Value::Null,
+        }
+    }
+
+    /// Resolves a `$ref` relative to `file`, returning the file and the name of the schema.
+    fn resolve<'a>(&self, file: &'a str, reference: &'a str) -> (&'a str, &'a str) {
+        let (target, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+        let target = if target.is_empty() { file } else { target };
+        let name = pointer.rsplit('/').next().unwrap_or_default();
+        (target, name)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    /// Payload sent by the client; `readOnly` properties are omitted.
+    Request,
+    /// Payload received by the client; `writeOnly` properties are omitted.
+    Response,
+}
+
+struct Generator<'a> {
+    specs: &'a Specs,
+    direction: Direction,
+}
+
+impl Generator<'_> {
+    fn named(&self, file: &str, name: &str, depth: usize) -> Value {
+        let schema = self.specs.schema(file, name);
+        let mut example = self.example(file, schema, "", depth);
+        if let (Some(property), Some(object)) = (
+            self.inherited_discriminator(file, schema),
+            example.as_object_mut(),
+        ) {
+            object.insert(property, Value::String(name.into()));
+        }
+        example
+    }
+
+    /// Discriminator property of a schema composed from a polymorphic base.
+    fn inherited_discriminator(&self, file: &str, schema: &Value) -> Option<String> {
+        schema["allOf"].as_array()?.iter().find_map(|member| {
+            let (file, name) = self.specs.resolve(file, member["$ref"].as_str()?);
+            let base = self.specs.schema(file, name);
+            base["discriminator"]["propertyName"]
+                .as_str()
+                .map(String::from)
+                .or_else(|| self.inherited_discriminator(file, base))
+        })
+    }
+
+    fn example(&self, file: &str, schema: &Value, name: &str, depth: usize) -> Value {
+        if depth > MAX_DEPTH {
+            return Value::Null;
+        }
+        if let Some(reference) = schema["$ref"].as_str() {
+            let (file, name) = self.specs.resolve(file, reference);
+            return self.named(file, name, depth + 1);
+        }
+        if let Some(example) = schema.get("example") {
+            return example.clone();
+        }
+        if let Some(value) = schema["enum"].as_array().and_then(|values| values.first()) {
+            return value.clone();
+        }
+        if let Some(members) = schema["allOf"].as_array() {
+            let mut merged = Map::new();
+            for member in members {
+                if let Value::Object(object) = self.example(file, member, name, depth + 1) {
+                    merged.extend(object);
+                }
+            }
+            if let Value::Object(object) = self.object(file, schema, name, depth) {
+                merged.extend(object);
+            }
+            return Value::Object(merged);
+        }
+        if let Some(first) = ["oneOf", "anyOf"]
+            .iter()
+            .find_map(|key| schema[key].as_array().and_then(|a| a.first()))
+        {
+            return self.example(file, first, name, depth + 1);
+        }
+
+        match schema["type"].as_str() {
+            Some("string") => Value::String(string_example(name, schema["format"].as_str())),
+            Some("integer") | Some("number") => Value::from(1),
+            Some("boolean") => Value::Bool(true),
+            Some("array") => {
+                Value::Array(vec![self.example(file, &schema["items"], name, depth + 1)])
+            }
+            Some("object") | None => self.object(file, schema, name, depth),
+            Some(_) => Value::Null,
+        }
+    }
+
+    /// Values of additional properties are named after the object itself.
+    fn object(&self, file: &str, schema: &Value, name: &str, depth: usize) -> Value {
+        let mut object = Map::new();
+        if let Some(properties) = schema["properties"].as_object() {
+            for (property_name, property) in properties {
+                let skip = match self.direction {
+                    Direction::Request => "readOnly",
+                    Direction::Response => "writeOnly",
+                };
+                if property[skip].as_bool() == Some(true) {
+                    continue;
+                }
+                object.insert(
+                    property_name.clone(),
+                    self.example(file, property, property_name, depth + 1),
+                );
+            }
+        }
+        if let Some(values) = schema["additionalProperties"]
+            .as_object()
+            .filter(|schema| !schema.is_empty())
+        {
+            let values = Value::Object(values.clone());
+            object.insert("key".into(), self.example(file, &values, name, depth + 1));
+        }
+        Value::Object(object)
+    }
+}
+
+/// Guesses a valid value of a string property without an example from its name.
+fn string_example(name: &str, format: Option<&str>) -> String {
+    let lower = name.to_lowercase();
+    let value = match format {
+        Some("date-time") => "2020-01-01T00:00:00Z",
+        Some("date") => "2020-01-01",
+        Some("uri") => "http://127.0.0.1/",
+        // a valid secp256k1 key, as the `sgx` feature parses keys
+        Some("hex") if name.ends_with("PubKey") => {
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        }
+        Some("hex") => "00",
+        _ if name.ends_with("Ts") => "2020-01-01T00:00:00",
+        _ if lower.ends_with("ip") || ["gateway", "hosts"].contains(&lower.as_str()) => "10.0.0.1",
+        _ if lower == "mask" => "255.255.255.0",
+        _ if [
+            "amount",
+            "price",
+            "balance",
+            "total",
+            "spent",
+            "remaining",
+            "reserved",
+        ]
+        .iter()
+        .any(|word| lower.contains(word)) =>
+        {
+            "1"
+        }
+        _ if lower.ends_with("id") || ["address", "identity"].contains(&lower.as_str()) => {
+            "0x0000000000000000000000000000000000000001"
+        }
+        _ => "string",
+    };
+    value.into()
+}
+
+struct Report<'a> {
+    specs: &'a Specs,
+    issues: BTreeSet<String>,
+}
+
+impl Report<'_> {
+    /// Round-trips an example of a spec schema through the model type `T`.
+    fn check<T: DeserializeOwned + Serialize>(
+        &mut self,
+        direction: Direction,
+        file: &str,
+        schema: &str,
+    ) {
+        self.check_with::<T>(direction, file, schema, |_| (), |_| None)
+    }
+
+    /// Like [`check`](Self::check), but with the generated example altered by `patch`.
+    fn check_patched<T: DeserializeOwned + Serialize>(
+        &mut self,
+        direction: Direction,
+        file: &str,
+        schema: &str,
+        patch: impl FnOnce(&mut Value),
+    ) {
+        self.check_with::<T>(direction, file, schema, patch, |_| None)
+    }
+
+    /// Like [`check`](Self::check), but also reports events of spec types
+    /// which are deserialized as unknown.
+    fn check_event<T: ApiEvent + DeserializeOwned + Serialize>(
+        &mut self,
+        direction: Direction,
+        file: &str,
+        schema: &str,
+    ) {
+        self.check_with::<T>(
+            direction,
+            file,
+            schema,
+            |_| (),
+            |event| event.unknown_event_type().map(String::from),
+        )
+    }
+
+    fn check_with<T: DeserializeOwned + Serialize>(
+        &mut self,
+        direction: Direction,
+        file: &str,
+        schema: &str,
+        patch: impl FnOnce(&mut Value),
+        unknown_event_type: impl Fn(&T) -> Option<String>,
+    ) {
+        let ty = std::any::type_name::<T>();
+        let ty = ty.rsplit("::").next().unwrap_or(ty);
+        let context = format!("{}#{} -> {}", file, schema, ty);
+        if self.specs.schema(file, schema).is_null() {
+            self.issues
+                .insert(format!("{}: schema not found in spec", context));
+            return;
+        }
+
+        let generator = Generator {
+            specs: self.specs,
+            direction,
+        };
+        let mut example = generator.named(file, schema, 0);
+        patch(&mut example);
+        let actual = match serde_json::from_value::<T>(example.clone()) {
+            Ok(value) => {
+                if let Some(event_type) = unknown_event_type(&value) {
+                    self.issues.insert(format!(
+                        "{}: deserializes as unknown event type {}",
+                        context, event_type
+                    ));
+                    return;
+                }
+                serde_json::to_value(value).unwrap()
+            }
+            Err(e) => {
+                self.issues
+                    .insert(format!("{}: does not deserialize: {}", context, e));
+                return;
+            }
+        };
+        compare(&context, "$", &example, &actual, &mut self.issues);
+    }
+
+    /// Reports spec endpoints whose paths do not occur in the client sources.
+    fn check_endpoints(&mut self) {
+        for (file, sources) in API_SOURCES {
+            let templates: Vec<Vec<String>> = sources
+                .iter()
+                .flat_map(|source| url_literals(&root().join(source)))
+                .map(|template| segments(&template))
+                .collect();
+            let paths = self.specs.0[file]["paths"].as_object().unwrap();
+            for path in paths.keys() {
+                let path_segments = segments(path);
+                let implemented = templates
+                    .iter()
+                    .any(|template| path_segments.ends_with(template));
+                if !implemented {
+                    self.issues
+                        .insert(format!("{} {}: endpoint not implemented", file, path));
+                }
+            }
+        }
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn compare(
+    context: &str,
+    path: &str,
+    expected: &Value,
+    actual: &Value,
+    issues: &mut BTreeSet<String>,
+) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let field = format!("{}.{}", path, key);
+                if let Some(actual) = actual.get(key) {
+                    compare(context, &field, value, actual, issues);
+                } else if let Some(other) = actual.keys().find(|k| k.eq_ignore_ascii_case(key)) {
+                    issues.insert(format!(
+                        "{}: wrong casing of {} (model uses `{}`)",
+                        context, field, other
+                    ));
+                } else if !value.is_null() {
+                    issues.insert(format!("{}: missing field {}", context, field));
+                }
+            }
+            for (key, value) in actual {
+                let known = expected.keys().any(|k| k.eq_ignore_ascii_case(key));
+                if !known && !value.is_null() {
+                    issues.insert(format!("{}: extra field {}.{}", context, path, key));
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if let (Some(expected), Some(actual)) = (expected.first(), actual.first()) {
+                compare(context, &format!("{}[0]", path), expected, actual, issues);
+            }
+        }
+        (Value::Null, _) | (_, Value::Null) => (),
+        _ if kind(expected) != kind(actual) => {
+            issues.insert(format!(
+                "{}: type of {} is {} instead of {}",
+                context,
+                path,
+                kind(actual),
+                kind(expected)
+            ));
+        }
+        _ => (),
+    }
+}
+
+/// Path segments with parameter names erased, eg. `/demands/{}/events`.
+fn segments(path: &str) -> Vec<String> {
+    path.split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| if s.starts_with('{') { "{}" } else { s }.to_string())
+        .collect()
+}
+
+/// String literals passed to the url building functions of the client.
+fn url_literals(path: &Path) -> Vec<String> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+        return entries.iter().flat_map(|p| url_literals(p)).collect();
+    }
+    let source = fs::read_to_string(path).unwrap();
+    const CALLS: &[&str] = &[
+        "url_format!(",
+        "url_format_obj(",
+        "format!(",
+        ".get(",
+        ".post(",
+        ".put(",
+        ".delete(",
+        ".ws(",
+        ".event_stream(",
+        "PATH: &'static str =",
+    ];
+    let mut literals = Vec::new();
+    for call in CALLS {
+        for (pos, _) in source.match_indices(call) {
+            let rest = source[pos + call.len()..].trim_start();
+            let rest = rest.strip_prefix('&').unwrap_or(rest);
+            if let Some(literal) = rest
+                .strip_prefix('"')
+                .and_then(|rest| rest.split_once('"'))
+                .map(|(literal, _)| literal)
+            {
+                if !literal.is_empty() && !literal.contains(' ') && !literal.contains(':') {
+                    literals.push(literal.to_string());
+                }
+            }
+        }
+    }
+    literals
+}
+
+#[test]
+fn spec_conformance() {
+    use Direction::{Request, Response};
+
+    let specs = Specs::load();
+    let mut report = Report {
+        specs: &specs,
+        issues: BTreeSet::new(),
+    };
+
+    const COMMON: &str = "common.yaml";
+    report.check::<ErrorMessage>(Response, COMMON, "ErrorMessage");
+    report.check::<market::Reason>(Request, COMMON, "Reason");
+    report.check::<Identity>(Response, "identity-api.yaml", "Identity");
+    report.check::<version::VersionInfo>(Response, "version-api.yaml", "YagnaVersionInfo");
+    report.check::<version::Version>(Response, "version-api.yaml", "YagnaVersionResponse");
+
+    const MARKET: &str = "market-api.yaml";
+    report.check::<market::NewDemand>(Request, MARKET, "DemandOfferBase");
+    report.check::<market::Demand>(Response, MARKET, "Demand");
+    report.check::<market::Offer>(Response, MARKET, "Offer");
+    report.check::<market::Proposal>(Response, MARKET, "Proposal");
+    report.check::<market::AgreementProposal>(Request, MARKET, "AgreementProposal");
+    report.check::<market::Agreement>(Response, MARKET, "Agreement");
+    report.check::<market::AgreementListEntry>(Response, MARKET, "AgreementListEntry");
+    report.check::<market::PropertyQuery>(Response, MARKET, "PropertyQuery");
+    report.check::<market::scan::NewScan>(Request, MARKET, "NewScan");
+    for event in [
+        "ProposalEvent",
+        "ProposalRejectedEvent",
+        "AgreementEvent",
+        "PropertyQueryEvent",
+    ] {
+        report.check_event::<market::RequestorEvent>(Response, MARKET, event);
+    }
+    for event in [
+        "AgreementApprovedEvent",
+        "AgreementRejectedEvent",
+        "AgreementCancelledEvent",
+        "AgreementTerminatedEvent",
+    ] {
+        report.check_event::<market::AgreementOperationEvent>(Response, MARKET, event);
+    }
+
+    const ACTIVITY: &str = "activity-api.yaml";
+    report.check::<activity::CreateActivityRequest>(Request, ACTIVITY, "CreateActivityRequest");
+    report.check::<activity::CreateActivityResult>(Response, ACTIVITY, "CreateActivityResult");
+    report.check::<activity::ExeScriptRequest>(Request, ACTIVITY, "ExeScriptRequest");
+    for command in [
+        "DeployCommand",
+        "StartCommand",
+        "TransferCommand",
+        "RunCommand",
+        "SignCommand",
+        "TerminateCommand",
+    ] {
+        report.check::<activity::ExeScriptCommand>(Request, ACTIVITY, command);
+    }
+    report.check::<activity::ExeScriptCommandState>(Response, ACTIVITY, "ExeScriptCommandState");
+    report.check::<activity::ExeScriptCommandResult>(Response, ACTIVITY, "ExeScriptCommandResult");
+    report.check_event::<activity::RuntimeEvent>(Response, ACTIVITY, "RuntimeEvent");
+    report.check::<activity::ActivityUsage>(Response, ACTIVITY, "ActivityUsage");
+    report.check::<activity::ActivityState>(Response, ACTIVITY, "ActivityState");
+    for event in [
+        "CreateActivity",
+        "DestroyActivity",
+        "GetActivityState",
+        "GetActivityUsage",
+    ] {
+        report.check_event::<activity::ProviderEvent>(Response, ACTIVITY, event);
+    }
+
+    const PAYMENT: &str = "payment-api.yaml";
+    report.check::<payment::NewDebitNote>(Request, PAYMENT, "DebitNote");
+    report.check::<payment::DebitNote>(Response, PAYMENT, "DebitNote");
+    report.check::<payment::NewInvoice>(Request, PAYMENT, "Invoice");
+    report.check::<payment::Invoice>(Response, PAYMENT, "Invoice");
+    report.check::<payment::Acceptance>(Request, PAYMENT, "Acceptance");
+    report.check::<payment::Rejection>(Request, PAYMENT, "Rejection");
+    report.check::<payment::NewAllocation>(Request, PAYMENT, "Allocation");
+    // The spec shares `Allocation` between requests and responses, so `paymentPlatform`
+    // may be a `PaymentPlatform` object. The daemon always responds with the resolved
+    // platform name, which is what `Allocation::payment_platform` holds.
+    report.check::<payment::Allocation>(Response, PAYMENT, "Allocation");
+    // With the platform name the daemon responds with, fields missing from the spec show up.
+    report.check_patched::<payment::Allocation>(Response, PAYMENT, "Allocation", |example| {
+        example["paymentPlatform"] = "erc20-holesky-tglm".into()
+    });
+    report.check::<payment::AllocationUpdate>(Request, PAYMENT, "AllocationUpdate");
+    report.check::<payment::Payment>(Response, PAYMENT, "Payment");
+    report.check::<payment::AgreementPayment>(Response, PAYMENT, "AgreementPayment");
+    report.check::<payment::ActivityPayment>(Response, PAYMENT, "ActivityPayment");
+    report.check::<payment::DriverStatusProperty>(Response, PAYMENT, "DriverStatusProperty");
+    report.check::<payment::Account>(Response, PAYMENT, "Account");
+    report.check::<payment::MarketDecoration>(Response, PAYMENT, "MarketDecoration");
+    for event in [
+        "DebitNoteReceivedEvent",
+        "DebitNoteAcceptedEvent",
+        "DebitNoteRejectedEvent",
+        "DebitNoteFailedEvent",
+        "DebitNoteSettledEvent",
+        "DebitNoteCancelledEvent",
+        "DebitNotePaymentStatusEvent",
+        "DebitNotePaymentOkEvent",
+    ] {
+        report.check_event::<payment::DebitNoteEvent>(Response, PAYMENT, event);
+    }
+    for event in [
+        "InvoiceReceivedEvent",
+        "InvoiceAcceptedEvent",
+        "InvoiceRejectedEvent",
+        "InvoiceFailedEvent",
+        "InvoiceSettledEvent",
+        "InvoiceCancelledEvent",
+        "InvoicePaymentStatusEvent",
+        "InvoicePaymentOkEvent",
+    ] {
+        report.check_event::<payment::InvoiceEvent>(Response, PAYMENT, event);
+    }
+
+    for file in ["net-api.yaml", "net-api-v2.yaml"] {
+        report.check::<net::Network>(Response, file, "Network");
+        report.check::<net::Node>(Request, file, "Node");
+        report.check::<net::Address>(Request, file, "Address");
+        report.check::<net::Connection>(Response, file, "Connection");
+    }
+    report.check::<net::NewNetwork>(Request, "net-api.yaml", "NewNetwork");
+    report.check::<net::Status>(Response, "net-api-v2.yaml", "Status");
+
+    report.check_endpoints();
+
+    let issues = report.issues;
+    let known_path = root().join(KNOWN_ISSUES);
+    if std::env::var_os("SPEC_CONFORMANCE_BLESS").is_some() {
+        let mut contents = issues.iter().cloned().collect::<Vec<_>>().join("\n");
+        contents.push('\n');
+        fs::write(&known_path, contents).unwrap();
+        return;
+    }
+
+    let known = fs::read_to_string(&known_path).unwrap_or_default();
+    let known: BTreeSet<&str> = known.lines().filter(|l| !l.is_empty()).collect();
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    let fixed: Vec<_> = known
+        .iter()
+        .filter(|issue| !issues.contains(**issue))
+        .collect();
+    if !fixed.is_empty() {
+        println!("\nresolved, remove from {}:", KNOWN_ISSUES);
+        fixed.iter().for_each(|issue| println!("  {}", issue));
+    }
+    let new: Vec<_> = issues
+        .iter()
+        .filter(|issue| !known.contains(issue.as_str()))
+        .collect();
+    assert!(
+        new.is_empty(),
+        "new spec conformance issues:\n  {}",
+        new.iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join("\n  ")
+    );
+}